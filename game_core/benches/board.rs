@@ -0,0 +1,107 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use game_core::{Board, Cell, Tetromino, TetrominoKind, RotationDirection};
+
+const KINDS: [TetrominoKind; 7] = [TetrominoKind::I, TetrominoKind::J, TetrominoKind::L, TetrominoKind::O, TetrominoKind::S, TetrominoKind::T, TetrominoKind::Z];
+
+//A messy stack about half the board high with every other row full
+fn stacked_board() -> Board
+{
+    let mut board = Board::new(10, 20);
+    for y in 10..20
+    {
+        for x in 0..10
+        {
+            if y % 2 == 0 || (x * 7 + y * 3) % 5 != 0
+            {
+                board.set_cell(x, y, Cell::Occupied(TetrominoKind::I));
+            }
+        }
+    }
+    board
+}
+
+//Every rotation of every piece at every column, the placements a bot would look at
+fn placements(board: &Board) -> Vec<Tetromino>
+{
+    let mut pieces = Vec::new();
+    for kind in KINDS.iter()
+    {
+        let mut piece = Tetromino::from_kind(*kind);
+        for _ in 0..4
+        {
+            for x in -2..board.width() as i32
+            {
+                let mut placed = piece.clone();
+                placed.position.x = x;
+                placed.position.y = 0;
+                pieces.push(placed);
+            }
+            piece.rotate(&Board::new(10, 20), RotationDirection::Clockwise);
+        }
+    }
+    pieces
+}
+
+//The layout before the bitboard, one bounds check and cell lookup per point
+fn cell_collision(board: &Board, piece: &Tetromino) -> bool
+{
+    piece.points.iter().any(|point|
+    {
+        let (x, y) = (point.x + piece.position.x, point.y + piece.position.y);
+        if x < 0 || x >= board.width() as i32 || y >= board.height() as i32
+        {
+            return true;
+        }
+        y >= 0 && *board.get_cell(x as u32, y as u32) != Cell::Empty
+    })
+}
+fn cell_drop_distance(board: &Board, piece: &Tetromino) -> i32
+{
+    let mut temp = piece.clone();
+    let mut distance = 0;
+    loop
+    {
+        temp.position.y += 1;
+        if cell_collision(board, &temp)
+        {
+            return distance;
+        }
+        distance += 1;
+    }
+}
+fn cell_full_rows(board: &Board) -> usize
+{
+    (0..board.height()).filter(|y| (0..board.width()).all(|x| *board.get_cell(x, *y) != Cell::Empty)).count()
+}
+
+fn collision(c: &mut Criterion)
+{
+    let board = stacked_board();
+    let pieces = placements(&board);
+    let mut group = c.benchmark_group("collision");
+    group.bench_function("cells", |b| b.iter(|| pieces.iter().filter(|piece| cell_collision(&board, black_box(piece))).count()));
+    group.bench_function("bitboard", |b| b.iter(|| pieces.iter().filter(|piece| board.check_collision(black_box(piece))).count()));
+    group.finish();
+}
+
+fn drop_position(c: &mut Criterion)
+{
+    let board = stacked_board();
+    let pieces: Vec<Tetromino> = placements(&board).into_iter().filter(|piece| !board.check_collision(piece)).collect();
+    let mut group = c.benchmark_group("drop_position");
+    group.bench_function("cells", |b| b.iter(|| pieces.iter().map(|piece| cell_drop_distance(&board, black_box(piece))).sum::<i32>()));
+    group.bench_function("bitboard", |b| b.iter(|| pieces.iter().map(|piece| board.drop_distance(black_box(piece))).sum::<i32>()));
+    group.finish();
+}
+
+fn full_rows(c: &mut Criterion)
+{
+    let board = stacked_board();
+    let mut group = c.benchmark_group("full_rows");
+    group.bench_function("cells", |b| b.iter(|| cell_full_rows(black_box(&board))));
+    group.bench_function("bitboard", |b| b.iter(|| (0..board.height()).filter(|y| black_box(&board).bits().is_row_full(*y)).count()));
+    group.finish();
+}
+
+criterion_group!(benches, collision, drop_position, full_rows);
+criterion_main!(benches);
@@ -0,0 +1,98 @@
+use std::env;
+use std::process;
+use std::thread;
+use std::time::Instant;
+use game_core::Bot;
+use game_core::simulation::{self, GameStats, Summary};
+
+//More threads than any machine has cores only costs memory
+const MAX_THREADS: u64 = 1024;
+const USAGE: &str = "usage: simulate [--games <n>] [--seed <first seed>] [--pieces <max per game>] [--format json|csv] [--threads <n>]";
+
+struct Options
+{
+    games: u64,
+    seed: u64,
+    pieces: u32,
+    csv: bool,
+    threads: u64
+}
+
+fn parse_options() -> Result<Options, String>
+{
+    let mut options = Options
+    {
+        games: 100,
+        seed: 0,
+        pieces: 1000,
+        csv: false,
+        threads: thread::available_parallelism().map_or(1, |n| n.get() as u64)
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next()
+    {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str()
+        {
+            "--games" => options.games = value()?.parse().map_err(|_| "--games needs a number")?,
+            "--seed" => options.seed = value()?.parse().map_err(|_| "--seed needs a number")?,
+            "--pieces" => options.pieces = value()?.parse().map_err(|_| "--pieces needs a number")?,
+            "--threads" => options.threads = value()?.parse().ok().filter(|threads| (1..=MAX_THREADS).contains(threads)).ok_or_else(|| format!("--threads needs a number from 1 to {}", MAX_THREADS))?,
+            "--format" => options.csv = match value()?.as_str()
+            {
+                "json" => false,
+                "csv" => true,
+                other => return Err(format!("unknown format {}", other))
+            },
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("unknown argument {}\n{}", other, USAGE))
+        }
+    }
+    //The last seed played is seed + games - 1
+    if options.seed.checked_add(options.games.saturating_sub(1)).is_none()
+    {
+        return Err(format!("--seed {} with --games {} runs past the largest seed {}", options.seed, options.games, u64::MAX));
+    }
+    //No more threads than there are games to give them
+    options.threads = options.threads.clamp(1, options.games.max(1));
+    Ok(options)
+}
+
+//Plays seeds seed..seed + games with the built in bot, split over threads, results in seed order
+fn main()
+{
+    let options = match parse_options()
+    {
+        Ok(options) => options,
+        Err(e) =>
+        {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+
+    let bot = Bot::default();
+    let start = Instant::now();
+    let workers: Vec<_> = (0..options.threads).map(|worker|
+    {
+        let bot = bot.clone();
+        let (first, games, threads, pieces) = (options.seed, options.games, options.threads, options.pieces);
+        thread::spawn(move ||
+        {
+            (worker..games).step_by(threads as usize).map(|i| simulation::play_game(&bot, first + i, pieces)).collect::<Vec<GameStats>>()
+        })
+    }).collect();
+    let mut games: Vec<GameStats> = workers.into_iter().flat_map(|worker| worker.join().expect("simulation thread panicked")).collect();
+    let elapsed = start.elapsed();
+    games.sort_by_key(|game| game.seed);
+
+    let summary = Summary::new(&games, elapsed);
+    if options.csv
+    {
+        println!("{}", summary.to_csv(&games));
+    }
+    else
+    {
+        println!("{}", summary.to_json(&games));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::tetromino::Tetromino;
+
+//Occupancy only, one bit per column in a u32 per row. Bit x is column x, row 0 is the top
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitBoard
+{
+    width: u32,
+    height: u32,
+    full_row: u32,
+    rows: Vec<u32>
+}
+impl BitBoard
+{
+    pub const MAX_WIDTH: u32 = 32;
+
+    pub fn new(width: u32, height: u32) -> BitBoard
+    {
+        assert!(width > 0 && width <= BitBoard::MAX_WIDTH, "board width must be between 1 and {}", BitBoard::MAX_WIDTH);
+        BitBoard{ width, height, full_row: u32::MAX >> (32 - width), rows: vec![0; height as usize] }
+    }
+    pub fn width(&self) -> u32
+    {
+        self.width
+    }
+    pub fn height(&self) -> u32
+    {
+        self.height
+    }
+    pub fn row(&self, y: u32) -> u32
+    {
+        self.rows[y as usize]
+    }
+    pub fn is_occupied(&self, x: u32, y: u32) -> bool
+    {
+        self.rows[y as usize] & (1 << x) != 0
+    }
+    pub fn set(&mut self, x: u32, y: u32, occupied: bool)
+    {
+        if occupied
+        {
+            self.rows[y as usize] |= 1 << x;
+        }
+        else
+        {
+            self.rows[y as usize] &= !(1 << x);
+        }
+    }
+    pub fn is_row_full(&self, y: u32) -> bool
+    {
+        self.rows[y as usize] == self.full_row
+    }
+    pub fn is_empty(&self) -> bool
+    {
+        self.rows.iter().all(|row| *row == 0)
+    }
+    //Anything outside the board collides, the top included
+    pub fn collides(&self, piece: &Tetromino) -> bool
+    {
+        piece.points.iter().any(|point|
+        {
+            let (x, y) = (point.x + piece.position.x, point.y + piece.position.y);
+            if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32
+            {
+                return true;
+            }
+            self.rows[y as usize] & (1 << x) != 0
+        })
+    }
+    //How many rows the piece can fall before it lands, tests a whole piece row per AND
+    pub fn drop_distance(&self, piece: &Tetromino) -> i32
+    {
+        let mask = match self.piece_mask(piece)
+        {
+            Some(mask) => mask,
+            None => return 0
+        };
+        let mut distance = 0;
+        while !self.mask_collides(&mask, distance + 1)
+        {
+            distance += 1;
+        }
+        distance
+    }
+    //The piece as one bitmask per row it covers, None if it pokes through a wall
+    fn piece_mask(&self, piece: &Tetromino) -> Option<PieceMask>
+    {
+        let top = piece.points.iter().map(|point| point.y).min().unwrap_or(0);
+        let mut mask = PieceMask{ top: top + piece.position.y, height: 0, rows: [0; 4] };
+        for point in piece.points.iter()
+        {
+            let x = point.x + piece.position.x;
+            if x < 0 || x >= self.width as i32
+            {
+                return None;
+            }
+            let row = (point.y - top) as usize;
+            mask.rows[row] |= 1 << x;
+            mask.height = mask.height.max(row as i32 + 1);
+        }
+        Some(mask)
+    }
+    fn mask_collides(&self, mask: &PieceMask, offset: i32) -> bool
+    {
+        let top = mask.top + offset;
+        if top < 0 || top + mask.height > self.height as i32
+        {
+            return true;
+        }
+        (0..mask.height).any(|row| self.rows[(top + row) as usize] & mask.rows[row as usize] != 0)
+    }
+    //Removes full rows and returns them top to bottom
+    pub fn clear_rows(&mut self) -> Vec<u32>
+    {
+        let full_row = self.full_row;
+        let cleared: Vec<u32> = (0..self.height).filter(|y| self.rows[*y as usize] == full_row).collect();
+        if !cleared.is_empty()
+        {
+            self.rows.retain(|row| *row != full_row);
+            let mut rows = vec![0; cleared.len()];
+            rows.append(&mut self.rows);
+            self.rows = rows;
+        }
+        cleared
+    }
+    //Every row except the hole column set
+    pub fn garbage_row(&self, hole: u32) -> u32
+    {
+        assert!(hole < self.width, "garbage hole {} is outside a board {} wide", hole, self.width);
+        self.full_row & !(1 << hole)
+    }
+    //Shifts the board up and adds `rows` at the bottom, the last one lowest.
+    //Returns true if a filled row was pushed out the top
+    pub fn push_rows(&mut self, rows: &[u32]) -> bool
+    {
+        let count = rows.len().min(self.height as usize);
+        let overflow = count < rows.len() || self.rows[..count].iter().any(|row| *row != 0);
+        self.rows.drain(..count);
+        self.rows.extend_from_slice(&rows[rows.len() - count..]);
+        overflow
+    }
+}
+
+struct PieceMask
+{
+    top: i32,
+    height: i32,
+    rows: [u32; 4]
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn full_rows_clear_and_shift_down()
+    {
+        let mut bits = BitBoard::new(4, 4);
+        for x in 0..4
+        {
+            bits.set(x, 1, true);
+            bits.set(x, 3, true);
+        }
+        bits.set(2, 2, true);
+        assert!(bits.is_row_full(1) && !bits.is_row_full(2));
+        assert_eq!(vec![1, 3], bits.clear_rows());
+        assert!(bits.is_occupied(2, 3));
+        assert_eq!(0b0100, bits.row(3));
+        assert_eq!(0, bits.row(2));
+    }
+
+    #[test]
+    fn pushed_rows_rise_from_the_bottom()
+    {
+        let mut bits = BitBoard::new(4, 4);
+        bits.set(0, 2, true);
+        assert!(!bits.push_rows(&[bits.garbage_row(1), bits.garbage_row(3)]));
+        assert!(bits.is_occupied(0, 0));
+        assert_eq!(0b1101, bits.row(2));
+        assert_eq!(0b0111, bits.row(3));
+        assert!(bits.push_rows(&[bits.garbage_row(0)]));
+        assert_eq!(0b1110, bits.row(3));
+    }
+
+    #[test]
+    fn collides_with_walls_top_floor_and_cells()
+    {
+        let mut bits = BitBoard::new(10, 20);
+        let mut piece = Tetromino::o();
+        piece.position.x = 0;
+        piece.position.y = -1;
+        assert!(bits.collides(&piece));
+        piece.position.y = 0;
+        assert!(!bits.collides(&piece));
+        assert_eq!(18, bits.drop_distance(&piece));
+        bits.set(1, 10, true);
+        assert_eq!(8, bits.drop_distance(&piece));
+        piece.position.x = -1;
+        assert!(bits.collides(&piece));
+        piece.position.x = 9;
+        assert!(bits.collides(&piece));
+    }
+}
@@ -0,0 +1,358 @@
+use crate::bitboard::BitBoard;
+use crate::tetromino::{Tetromino, TetrominoKind, Rotation, RotationDirection};
+use crate::utility::Point;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell
+{
+    Empty,
+    //The kind of piece the cell came from, colours are left to whoever draws it
+    Occupied(TetrominoKind),
+    //Sent by an opponent
+    Garbage
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TSpin
+{
+    None,
+    Mini,
+    Full
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearResult
+{
+    //Cleared rows, top to bottom, in board coordinates from before the clear
+    pub rows: Vec<u32>,
+    pub t_spin: TSpin,
+    pub perfect_clear: bool
+}
+impl ClearResult
+{
+    pub fn lines(&self) -> u32
+    {
+        self.rows.len() as u32
+    }
+}
+
+//Occupancy lives in a bitboard for fast collision and row checks, `cells` only carries the piece kinds.
+//The top `buffer` rows are hidden above the visible field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board
+{
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) buffer: u32,
+    pub(crate) bits: BitBoard,
+    pub(crate) cells: Vec<Cell>,
+}
+impl Board
+{
+    pub fn new(width: u32, height: u32) -> Board
+    {
+        Board::with_buffer(width, height, 0)
+    }
+    //`height` visible rows with `buffer` more on top of them
+    pub fn with_buffer(width: u32, height: u32, buffer: u32) -> Board
+    {
+        let height = height + buffer;
+        Board{ width, height, buffer, bits: BitBoard::new(width, height), cells: vec![Cell::Empty; (width * height) as usize] }
+    }
+    pub fn width(&self) -> u32
+    {
+        self.width
+    }
+    //All rows, the buffer included
+    pub fn height(&self) -> u32
+    {
+        self.height
+    }
+    pub fn visible_height(&self) -> u32
+    {
+        self.height - self.buffer
+    }
+    //Rows above the visible field, row `buffer` is the top visible row
+    pub fn buffer(&self) -> u32
+    {
+        self.buffer
+    }
+    //Centred with its bottom in the last buffer row, then dropped a row if there is room so it shows right away
+    pub fn spawn(&self, kind: TetrominoKind) -> Tetromino
+    {
+        let mut piece = Tetromino::from_kind(kind);
+        let left = piece.points.iter().map(|point| point.x).min().unwrap_or(0);
+        let right = piece.points.iter().map(|point| point.x).max().unwrap_or(0);
+        let top = piece.points.iter().map(|point| point.y).min().unwrap_or(0);
+        let bottom = piece.points.iter().map(|point| point.y).max().unwrap_or(0);
+        piece.position = Point{ x: (self.width as i32 - (right - left + 1)) / 2 - left, y: (self.buffer as i32 - 1 - bottom).max(-top) };
+        let mut below = piece.clone();
+        below.position.y += 1;
+        if !self.check_collision(&piece) && !self.check_collision(&below)
+        {
+            piece = below;
+        }
+        piece
+    }
+    pub fn bits(&self) -> &BitBoard
+    {
+        &self.bits
+    }
+    pub fn get_cell(&self, x: u32, y: u32) -> &Cell
+    {
+        &self.cells[(x + y * self.width) as usize]
+    }
+    pub fn set_cell(&mut self, x: u32, y: u32, cell: Cell)
+    {
+        self.bits.set(x, y, cell != Cell::Empty);
+        self.cells[(x + y * self.width) as usize] = cell;
+    }
+    //Entirely inside the hidden buffer, locking it there is a lock out
+    pub fn is_lock_out(&self, piece: &Tetromino) -> bool
+    {
+        piece.points.iter().all(|point| point.y + piece.position.y < self.buffer as i32)
+    }
+    pub fn check_collision(&self, piece: &Tetromino) -> bool
+    {   
+        self.bits.collides(piece)
+    }
+    pub fn drop_distance(&self, piece: &Tetromino) -> i32
+    {
+        self.bits.drop_distance(piece)
+    }
+    //Writes the piece into the board and clears any completed rows.
+    //`last_rotation` is the direction and kick index of the rotation that moved the piece last, if any
+    pub(crate) fn lock_piece(&mut self, piece: &Tetromino, last_rotation: Option<(RotationDirection, usize)>) -> ClearResult
+    {
+        let t_spin = match last_rotation
+        {
+            Some((direction, kick)) => self.detect_t_spin(piece, direction, kick),
+            None => TSpin::None
+        };
+        for point in piece.points.iter()
+        {
+            let position = *point + piece.position;
+            self.set_cell(position.x as u32, position.y as u32, Cell::Occupied(piece.kind));
+        }
+        ClearResult { t_spin, ..self.clear_lines() }
+    }
+    //Pushes a garbage row up from the bottom for every hole column, the last one ends up lowest.
+    //Returns true if filled cells were pushed out the top, which tops the player out
+    pub fn insert_garbage(&mut self, holes: &[u32]) -> bool
+    {
+        //Checked before anything moves, a hole outside the board would leave a row without one
+        assert!(holes.iter().all(|hole| *hole < self.width), "garbage holes must be inside a board {} wide", self.width);
+        let rows: Vec<u32> = holes.iter().map(|hole| self.bits.garbage_row(*hole)).collect();
+        let overflow = self.bits.push_rows(&rows);
+        let count = holes.len().min(self.height as usize);
+        let width = self.width as usize;
+        self.cells.drain(..count * width);
+        for hole in holes[holes.len() - count..].iter()
+        {
+            self.cells.extend((0..self.width).map(|x| if x == *hole { Cell::Empty } else { Cell::Garbage }));
+        }
+        overflow
+    }
+    fn is_blocked(&self, x: i32, y: i32) -> bool
+    {
+        if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32
+        {
+            return true;
+        }
+        self.bits.is_occupied(x as u32, y as u32)
+    }
+    //3-corner rule, full when both corners the T points at are filled or the last SRS kick (1x2) was used.
+    //180 kicks are SRS+ and none of them count as that kick
+    fn detect_t_spin(&self, piece: &Tetromino, direction: RotationDirection, kick: usize) -> TSpin
+    {
+        if piece.kind != TetrominoKind::T
+        {
+            return TSpin::None;
+        }
+        let center = piece.position + Point{ x: 1, y: 1 };
+        let corner = |x: i32, y: i32| self.is_blocked(center.x + x, center.y + y);
+        let (top_left, top_right, bottom_left, bottom_right) = (corner(-1, -1), corner(1, -1), corner(-1, 1), corner(1, 1));
+
+        let corners = [top_left, top_right, bottom_left, bottom_right].iter().filter(|blocked| **blocked).count();
+        if corners < 3
+        {
+            return TSpin::None;
+        }
+        let front = match piece.rotation
+        {
+            Rotation::Zero => top_left && top_right,
+            Rotation::Right => top_right && bottom_right,
+            Rotation::Two => bottom_left && bottom_right,
+            Rotation::Left => top_left && bottom_left
+        };
+        let last_kick = direction != RotationDirection::Half && kick == 4;
+        if front || last_kick { TSpin::Full } else { TSpin::Mini }
+    }
+    //Drops every remaining row down past the cleared ones, works for any set of rows
+    pub(crate) fn clear_lines(&mut self) -> ClearResult
+    {    
+        let rows = self.bits.clear_rows();
+        if !rows.is_empty()
+        {
+            let width = self.width as usize;
+            let mut cells = vec![Cell::Empty; rows.len() * width];
+            for y in (0..self.height).filter(|y| !rows.contains(y))
+            {
+                let start = y as usize * width;
+                cells.extend_from_slice(&self.cells[start..start + width]);
+            }
+            self.cells = cells;
+        }
+        let perfect_clear = !rows.is_empty() && self.bits.is_empty();
+        ClearResult { rows, t_spin: TSpin::None, perfect_clear }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const FILL: Cell = Cell::Occupied(TetrominoKind::I);
+
+    //Rows of '#' and '.', the last row is the bottom of the board
+    fn board_from(rows: &[&str]) -> Board
+    {
+        let mut board = Board::new(rows[0].len() as u32, rows.len() as u32);
+        for (y, row) in rows.iter().enumerate()
+        {
+            for (x, c) in row.chars().enumerate()
+            {
+                if c == '#'
+                {
+                    board.set_cell(x as u32, y as u32, FILL);
+                }
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn clears_non_contiguous_rows()
+    {
+        let mut board = board_from(&[
+            "....",
+            "#...",
+            "####",
+            ".#..",
+            "####",
+            "..#.",
+        ]);
+        let result = board.clear_lines();
+        assert_eq!(vec![2, 4], result.rows);
+        assert!(!result.perfect_clear);
+        assert_eq!(board_from(&[
+            "....",
+            "....",
+            "....",
+            "#...",
+            ".#..",
+            "..#.",
+        ]), board);
+    }
+
+    #[test]
+    fn detects_perfect_clear()
+    {
+        let mut board = board_from(&[
+            "....",
+            "####",
+            "####",
+        ]);
+        let result = board.clear_lines();
+        assert_eq!(2, result.lines());
+        assert!(result.perfect_clear);
+    }
+
+    #[test]
+    fn garbage_pushes_the_stack_up()
+    {
+        let mut board = board_from(&[
+            "....",
+            "....",
+            ".#..",
+        ]);
+        assert!(!board.insert_garbage(&[3, 0]));
+        let mut expected = board_from(&[
+            ".#..",
+            "###.",
+            ".###",
+        ]);
+        for (x, y) in [(0, 1), (1, 1), (2, 1), (1, 2), (2, 2), (3, 2)].iter()
+        {
+            expected.set_cell(*x, *y, Cell::Garbage);
+        }
+        assert_eq!(expected, board);
+        assert!(board.insert_garbage(&[2]));
+    }
+
+    #[test]
+    #[should_panic(expected = "garbage holes must be inside")]
+    fn garbage_hole_outside_the_board_panics()
+    {
+        let mut board = Board::new(10, 20);
+        board.insert_garbage(&[3, 10]);
+    }
+
+    fn t_piece(board: &Board, rotation: Rotation, position: Point) -> Tetromino
+    {
+        let mut piece = Tetromino::t();
+        while piece.rotation != rotation
+        {
+            piece.rotate(&Board::new(10, 10), RotationDirection::Clockwise);
+        }
+        piece.position = position;
+        assert!(!board.check_collision(&piece));
+        piece
+    }
+
+    #[test]
+    fn t_spin_double_is_full()
+    {
+        let mut board = board_from(&[
+            "......",
+            "##....",
+            "#...##",
+            "##.###",
+        ]);
+        let piece = t_piece(&board, Rotation::Two, Point{ x: 1, y: 1 });
+        let result = board.lock_piece(&piece, Some((RotationDirection::Clockwise, 0)));
+        assert_eq!(TSpin::Full, result.t_spin);
+        assert_eq!(2, result.lines());
+    }
+
+    #[test]
+    fn t_spin_mini_without_front_corners()
+    {
+        let mut board = board_from(&[
+            "......",
+            "...#..",
+            "#.....",
+            "##.###",
+        ]);
+        let piece = t_piece(&board, Rotation::Left, Point{ x: 1, y: 1 });
+        assert_eq!(TSpin::Mini, board.clone().lock_piece(&piece, Some((RotationDirection::Clockwise, 1))).t_spin);
+        assert_eq!(TSpin::Full, board.clone().lock_piece(&piece, Some((RotationDirection::CounterClockwise, 4))).t_spin);
+        //Index 4 of a 180 kick table is an ordinary kick
+        assert_eq!(TSpin::Mini, board.clone().lock_piece(&piece, Some((RotationDirection::Half, 4))).t_spin);
+        assert_eq!(TSpin::Mini, board.clone().lock_piece(&piece, Some((RotationDirection::Half, 5))).t_spin);
+        assert_eq!(TSpin::None, board.lock_piece(&piece, None).t_spin);
+    }
+
+    #[test]
+    fn two_corners_is_not_a_t_spin()
+    {
+        let mut board = board_from(&[
+            "......",
+            "......",
+            "......",
+            "##.###",
+        ]);
+        let piece = t_piece(&board, Rotation::Two, Point{ x: 1, y: 1 });
+        assert_eq!(TSpin::None, board.lock_piece(&piece, Some((RotationDirection::Half, 0))).t_spin);
+    }
+}
@@ -0,0 +1,500 @@
+use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::Entry;
+use crate::bitboard::BitBoard;
+use crate::board::Board;
+use crate::tetromino::{Tetromino, Rotation, RotationDirection};
+use crate::state::{GameState, Inputs};
+use crate::utility::Point;
+
+//One input the bot can give, each takes at least a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move
+{
+    Left,
+    Right,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    SoftDrop,
+    HardDrop,
+    Hold
+}
+impl Move
+{
+    const PIECE_MOVES: [Move; 6] = [Move::Left, Move::Right, Move::RotateCw, Move::RotateCcw, Move::Rotate180, Move::SoftDrop];
+
+    pub fn inputs(self) -> Inputs
+    {
+        match self
+        {
+            Move::Left => Inputs { horizontal: -1, ..Inputs::default() },
+            Move::Right => Inputs { horizontal: 1, ..Inputs::default() },
+            Move::RotateCw => Inputs { rotate_cw: true, ..Inputs::default() },
+            Move::RotateCcw => Inputs { rotate_ccw: true, ..Inputs::default() },
+            Move::Rotate180 => Inputs { rotate_180: true, ..Inputs::default() },
+            Move::SoftDrop => Inputs { soft_drop: true, ..Inputs::default() },
+            Move::HardDrop => Inputs { hard_drop: true, ..Inputs::default() },
+            Move::Hold => Inputs { hold: true, ..Inputs::default() }
+        }
+    }
+    //Applies the move to a free piece, false if it is blocked
+    fn apply(self, board: &Board, piece: &mut Tetromino) -> bool
+    {
+        let (offset, direction) = match self
+        {
+            Move::Left => (Point{ x: -1, y: 0 }, None),
+            Move::Right => (Point{ x: 1, y: 0 }, None),
+            Move::SoftDrop => (Point{ x: 0, y: 1 }, None),
+            Move::RotateCw => (Point::zero(), Some(RotationDirection::Clockwise)),
+            Move::RotateCcw => (Point::zero(), Some(RotationDirection::CounterClockwise)),
+            Move::Rotate180 => (Point::zero(), Some(RotationDirection::Half)),
+            Move::HardDrop | Move::Hold => return false
+        };
+        if let Some(direction) = direction
+        {
+            return piece.rotate(board, direction).is_some();
+        }
+        let origin = piece.position;
+        piece.position = origin + offset;
+        if board.check_collision(piece)
+        {
+            piece.position = origin;
+            return false;
+        }
+        true
+    }
+}
+
+//Heuristic weights, a placement scores the weighted sum of the features of the board it leaves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weights
+{
+    pub aggregate_height: f64,
+    pub holes: f64,
+    pub bumpiness: f64,
+    pub lines: f64
+}
+impl Default for Weights
+{
+    fn default() -> Weights
+    {
+        Weights { aggregate_height: -0.510066, holes: -0.35663, bumpiness: -0.184483, lines: 0.760666 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Placement
+{
+    //The piece where it will lock
+    pub piece: Tetromino,
+    pub moves: Vec<Move>,
+    pub score: f64,
+    //Where the piece should be after each move, to notice when the game did something else
+    path: Vec<(Point, Rotation)>
+}
+impl Placement
+{
+    pub fn uses_hold(&self) -> bool
+    {
+        self.moves.first() == Some(&Move::Hold)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bot
+{
+    pub weights: Weights
+}
+impl Bot
+{
+    pub fn new(weights: Weights) -> Bot
+    {
+        Bot { weights }
+    }
+    //Best placement for the active piece or, if hold is allowed, the piece it would swap to
+    pub fn plan(&self, state: &GameState) -> Option<Placement>
+    {
+        if state.is_game_over()
+        {
+            return None;
+        }
+        let board = state.board();
+        let mut best = self.best_placement(board, state.active_piece(), &[]);
+        if state.can_hold()
+        {
+            let kind = match state.hold_piece()
+            {
+                Some(piece) => Some(piece.kind),
+                None => state.next_pieces().next().copied()
+            };
+            let swapped = kind.map(|kind| board.spawn(kind)).filter(|piece| !board.check_collision(piece));
+            if let Some(piece) = swapped
+            {
+                let held = self.best_placement(board, &piece, &[Move::Hold]);
+                if held.as_ref().map(|held| held.score) > best.as_ref().map(|best| best.score)
+                {
+                    best = held;
+                }
+            }
+        }
+        best
+    }
+    //Every placement reachable from where the piece is now, soft drops followed by slides and spins included
+    pub fn placements(&self, board: &Board, piece: &Tetromino) -> Vec<Placement>
+    {
+        let mut nodes: Vec<(Tetromino, Option<(usize, Move)>)> = vec![(piece.clone(), None)];
+        let mut visited = StateGrid::new(board);
+        let mut landings = StateGrid::new(board);
+        visited.insert(piece);
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        let mut placements: HashMap<[(i32, i32); 4], Placement> = HashMap::new();
+        let mut order = Vec::new();
+
+        while let Some(index) = queue.pop_front()
+        {
+            let node = nodes[index].0.clone();
+            let mut landed = node.clone();
+            landed.position.y += board.drop_distance(&node);
+            //Symmetric pieces land on the same cells from different rotations, so those are compared too
+            if landings.insert(&landed)
+            {
+                let mut cells = landed.points.map(|point| (point.x + landed.position.x, point.y + landed.position.y));
+                cells.sort_unstable();
+                if let Entry::Vacant(entry) = placements.entry(cells)
+                {
+                    let (mut moves, path) = Bot::path_to(&nodes, index);
+                    moves.push(Move::HardDrop);
+                    order.push(*entry.key());
+                    entry.insert(Placement { piece: landed, moves, score: 0.0, path });
+                }
+            }
+
+            for movement in Move::PIECE_MOVES.iter()
+            {
+                let mut next = node.clone();
+                if movement.apply(board, &mut next) && visited.insert(&next)
+                {
+                    nodes.push((next, Some((index, *movement))));
+                    queue.push_back(nodes.len() - 1);
+                }
+            }
+        }
+        //Breadth first, so the first path found to a placement is the shortest
+        order.into_iter().filter_map(|cells| placements.remove(&cells)).collect()
+    }
+    fn path_to(nodes: &[(Tetromino, Option<(usize, Move)>)], mut index: usize) -> (Vec<Move>, Vec<(Point, Rotation)>)
+    {
+        let mut moves = Vec::new();
+        let mut path = Vec::new();
+        while let Some((parent, movement)) = nodes[index].1
+        {
+            moves.push(movement);
+            path.push((nodes[index].0.position, nodes[index].0.rotation));
+            index = parent;
+        }
+        moves.reverse();
+        path.reverse();
+        (moves, path)
+    }
+    fn best_placement(&self, board: &Board, piece: &Tetromino, prefix: &[Move]) -> Option<Placement>
+    {
+        let mut best: Option<Placement> = None;
+        for mut placement in self.placements(board, piece)
+        {
+            let score = match self.evaluate(board, &placement.piece)
+            {
+                Some(score) => score,
+                None => continue
+            };
+            if best.as_ref().is_none_or(|best| score > best.score)
+            {
+                placement.score = score;
+                best = Some(placement);
+            }
+        }
+        best.map(|mut placement|
+        {
+            let spawn = (piece.position, piece.rotation);
+            placement.moves.splice(0..0, prefix.iter().copied());
+            placement.path.splice(0..0, prefix.iter().map(|_| spawn));
+            placement
+        })
+    }
+    //Score of the board left after locking the piece, None if it would lock out
+    pub fn evaluate(&self, board: &Board, piece: &Tetromino) -> Option<f64>
+    {
+        if board.is_lock_out(piece)
+        {
+            return None;
+        }
+        let mut bits = board.bits().clone();
+        for point in piece.points.iter()
+        {
+            let (x, y) = (point.x + piece.position.x, point.y + piece.position.y);
+            if y < 0
+            {
+                return None;
+            }
+            bits.set(x as u32, y as u32, true);
+        }
+        let lines = bits.clear_rows().len();
+
+        //Top to bottom, a hole is an empty cell under anything seen in its column so far
+        let mut heights = [0; BitBoard::MAX_WIDTH as usize];
+        let (mut covered, mut holes) = (0u32, 0);
+        for y in 0..bits.height()
+        {
+            let row = bits.row(y);
+            holes += (covered & !row).count_ones();
+            let mut tops = row & !covered;
+            while tops != 0
+            {
+                heights[tops.trailing_zeros() as usize] = bits.height() - y;
+                tops &= tops - 1;
+            }
+            covered |= row;
+        }
+        let heights = &heights[..bits.width() as usize];
+        let aggregate_height: u32 = heights.iter().sum();
+        let bumpiness: u32 = heights.windows(2).map(|pair| (pair[0] as i32 - pair[1] as i32).unsigned_abs()).sum();
+
+        let weights = &self.weights;
+        Some(weights.aggregate_height * aggregate_height as f64 + weights.holes * holes as f64 + weights.bumpiness * bumpiness as f64 + weights.lines * lines as f64)
+    }
+}
+
+//One flag per position and rotation a piece can be in, much cheaper than hashing
+struct StateGrid
+{
+    width: i32,
+    height: i32,
+    flags: Vec<bool>
+}
+impl StateGrid
+{
+    //A piece's position can be up to this far outside the board while its cells are inside
+    const MARGIN: i32 = 4;
+
+    fn new(board: &Board) -> StateGrid
+    {
+        let width = board.width() as i32 + 2 * StateGrid::MARGIN;
+        let height = board.height() as i32 + 2 * StateGrid::MARGIN;
+        StateGrid { width, height, flags: vec![false; (width * height * 4) as usize] }
+    }
+    //False if the state was already in the grid
+    fn insert(&mut self, piece: &Tetromino) -> bool
+    {
+        let (x, y) = (piece.position.x + StateGrid::MARGIN, piece.position.y + StateGrid::MARGIN);
+        if x < 0 || y < 0 || x >= self.width || y >= self.height
+        {
+            return false;
+        }
+        let index = (x + (y + piece.rotation as i32 * self.height) * self.width) as usize;
+        !std::mem::replace(&mut self.flags[index], true)
+    }
+}
+
+//Turns a bot's plans into inputs one frame at a time, replanning whenever the game doesn't go as planned
+pub struct BotPlayer
+{
+    bot: Bot,
+    plan: Option<Placement>,
+    step: usize,
+    pieces_placed: u32,
+    horizontal: i32
+}
+impl BotPlayer
+{
+    pub fn new(bot: Bot) -> BotPlayer
+    {
+        BotPlayer { bot, plan: None, step: 0, pieces_placed: 0, horizontal: 0 }
+    }
+    pub fn plan(&self) -> Option<&Placement>
+    {
+        self.plan.as_ref()
+    }
+    //Forgets the current plan, for when the game was restarted
+    pub fn reset(&mut self)
+    {
+        self.plan = None;
+        self.horizontal = 0;
+    }
+    fn replan(&mut self, state: &GameState)
+    {
+        self.plan = self.bot.plan(state);
+        self.step = 0;
+        self.pieces_placed = state.pieces_placed();
+    }
+    //Whether the piece is where the previous move should have put it, the final hard drop has nothing to check
+    fn on_track(&self, state: &GameState) -> bool
+    {
+        let (position, rotation) = match &self.plan
+        {
+            Some(plan) if self.step > 0 => match plan.path.get(self.step - 1)
+            {
+                Some(step) => *step,
+                None => return true
+            },
+            _ => return true
+        };
+        let piece = state.active_piece();
+        piece.position.x == position.x && piece.rotation == rotation && piece.position.y <= position.y
+    }
+    pub fn next_inputs(&mut self, state: &GameState) -> Inputs
+    {
+        if state.is_game_over()
+        {
+            return Inputs::default();
+        }
+        if self.plan.is_none() || state.pieces_placed() != self.pieces_placed || !self.on_track(state)
+        {
+            self.replan(state);
+        }
+        let inputs = loop
+        {
+            let plan = match &self.plan
+            {
+                Some(plan) => plan,
+                None => break Move::HardDrop.inputs()
+            };
+            //Every move is sent, when inputs run ahead of the game (over a network) the lock just hasn't shown yet
+            let movement = match plan.moves.get(self.step)
+            {
+                Some(movement) => *movement,
+                None => break Inputs::default()
+            };
+            let inputs = movement.inputs();
+            match movement
+            {
+                //Let go for a frame between taps in the same direction so DAS doesn't kick in
+                Move::Left | Move::Right if inputs.horizontal == self.horizontal => break Inputs::default(),
+                Move::SoftDrop =>
+                {
+                    if state.active_piece().position.y < plan.path[self.step].0.y
+                    {
+                        break inputs;
+                    }
+                    self.step += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            self.step += 1;
+            break inputs;
+        };
+        self.horizontal = inputs.horizontal;
+        inputs
+    }
+    //Plays until game over or until `max_pieces` have been placed
+    pub fn play(&mut self, state: &mut GameState, max_pieces: u32)
+    {
+        while !state.is_game_over() && state.pieces_placed() < max_pieces
+        {
+            let inputs = self.next_inputs(state);
+            state.step_frame(&inputs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::board::Cell;
+    use crate::tetromino::TetrominoKind;
+    use crate::replay::Replay;
+
+    #[test]
+    fn finds_every_resting_place_on_an_empty_board()
+    {
+        let board = Board::new(10, 20);
+        let bot = Bot::default();
+        //O fits in 9 columns, I in 10 upright and 7 flat
+        assert_eq!(9, bot.placements(&board, &Tetromino::o()).len());
+        assert_eq!(17, bot.placements(&board, &Tetromino::i()).len());
+        assert_eq!(34, bot.placements(&board, &Tetromino::t()).len());
+    }
+
+    #[test]
+    fn reaches_a_tuck_under_an_overhang()
+    {
+        let mut board = Board::new(10, 20);
+        for x in 3..10
+        {
+            board.set_cell(x, 17, Cell::Occupied(TetrominoKind::I));
+        }
+        let bot = Bot::default();
+        let placements = bot.placements(&board, &Tetromino::o());
+        let tuck = placements.iter().find(|placement| placement.piece.position == Point{ x: 2, y: 18 }).expect("tuck not found");
+        assert!(tuck.moves.contains(&Move::SoftDrop));
+        assert_eq!(&[Move::Right, Move::HardDrop], &tuck.moves[tuck.moves.len() - 2..]);
+    }
+
+    #[test]
+    fn prefers_clearing_lines()
+    {
+        let mut board = Board::new(10, 20);
+        for y in 16..20
+        {
+            for x in 0..9
+            {
+                board.set_cell(x, y, Cell::Occupied(TetrominoKind::I));
+            }
+        }
+        let bot = Bot::default();
+        let best = bot.best_placement(&board, &Tetromino::i(), &[]).unwrap();
+        assert_eq!(Rotation::Right, best.piece.rotation);
+        assert!(best.piece.points.iter().all(|point| point.x + best.piece.position.x == 9));
+    }
+
+    #[test]
+    fn never_locks_out_in_the_buffer()
+    {
+        //Only a one wide well is left in the visible field, an O can only rest in the buffer above it
+        let mut board = Board::with_buffer(10, 20, 4);
+        for y in 4..24
+        {
+            for x in 0..9
+            {
+                board.set_cell(x, y, Cell::Occupied(TetrominoKind::I));
+            }
+        }
+        let bot = Bot::default();
+        let o = board.spawn(TetrominoKind::O);
+        assert!(!bot.placements(&board, &o).is_empty());
+        assert!(bot.best_placement(&board, &o, &[]).is_none());
+        let best = bot.best_placement(&board, &board.spawn(TetrominoKind::I), &[]).unwrap();
+        assert!(best.piece.points.iter().all(|point| point.x + best.piece.position.x == 9));
+    }
+
+    #[test]
+    fn plays_a_long_game()
+    {
+        let mut state = GameState::with_seed(7);
+        let mut player = BotPlayer::new(Bot::default());
+        player.play(&mut state, 100);
+        assert!(!state.is_game_over());
+        assert_eq!(100, state.pieces_placed());
+        assert!(state.score().lines() >= 30);
+    }
+
+    #[test]
+    fn recorded_frames_replay_the_same_game()
+    {
+        let mut state = GameState::with_seed(3);
+        let mut recording = Replay::new(state.seed(), *state.rules(), state.mode());
+        let mut player = BotPlayer::new(Bot::default());
+        for _ in 0..100
+        {
+            state.step_with(0.05, |state|
+            {
+                let inputs = player.next_inputs(state);
+                recording.record(GameState::FRAME_TIME as f32, &inputs);
+                inputs
+            });
+        }
+        assert!(state.pieces_placed() > 10);
+        assert_eq!(state.board(), recording.play().board());
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use crate::board::{ClearResult, TSpin};
+
+//How garbage is sent and where its holes go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GarbageRules
+{
+    //Chance in percent that the hole moves between two rows of the same attack, every attack starts in a new column
+    pub messiness: u32,
+    //Frames an attack waits before it can rise, time for the player to cancel it
+    pub delay: u32,
+    //Most rows that rise after one piece, the rest wait for the next
+    pub cap: u32
+}
+impl Default for GarbageRules
+{
+    fn default() -> GarbageRules
+    {
+        GarbageRules { messiness: 0, delay: 30, cap: 8 }
+    }
+}
+impl GarbageRules
+{
+    pub const MESSINESS: RangeInclusive<u32> = 0..=100;
+    //At least a row has to rise or garbage would wait forever
+    pub const CAP: RangeInclusive<u32> = 1..=40;
+
+    //Names the first value out of range, like `Rules::check`
+    pub fn check(&self) -> Result<(), String>
+    {
+        let values = [("messiness", self.messiness, GarbageRules::MESSINESS), ("cap", self.cap, GarbageRules::CAP)];
+        match values.iter().find(|(_, value, range)| !range.contains(value))
+        {
+            Some((name, value, range)) => Err(format!("garbage {} {} is not between {} and {}", name, value, range.start(), range.end())),
+            None => Ok(())
+        }
+    }
+}
+
+//Lines sent by a lock, guideline style. `back_to_back` is whether the chain was running before this clear
+pub fn attack_lines(result: &ClearResult, combo: i32, back_to_back: bool) -> u32
+{
+    const LINES: [u32; 5] = [0, 0, 1, 2, 4];
+    const T_SPIN_MINI: [u32; 3] = [0, 0, 1];
+    const T_SPIN: [u32; 4] = [0, 2, 4, 6];
+    const COMBO: [u32; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+    const PERFECT_CLEAR: u32 = 10;
+
+    let lines = result.lines().min(4);
+    if lines == 0
+    {
+        return 0;
+    }
+    let mut attack = match result.t_spin
+    {
+        TSpin::None => LINES[lines as usize],
+        TSpin::Mini => T_SPIN_MINI[lines.min(2) as usize],
+        TSpin::Full => T_SPIN[lines.min(3) as usize]
+    };
+    if back_to_back && (lines >= 4 || result.t_spin != TSpin::None)
+    {
+        attack += 1;
+    }
+    attack += COMBO[(combo.max(0) as usize).min(COMBO.len() - 1)];
+    if result.perfect_clear
+    {
+        attack += PERFECT_CLEAR;
+    }
+    attack
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Attack
+{
+    lines: u32,
+    waited: u32,
+    //Some of its rows have risen already
+    started: bool
+}
+
+//Garbage on its way to a board, oldest attack first. Holes come from a seeded generator so the same seed places them the same way
+#[derive(Debug, Clone)]
+pub struct GarbageQueue
+{
+    rules: GarbageRules,
+    attacks: VecDeque<Attack>,
+    rng: Pcg32,
+    hole: Option<u32>
+}
+impl GarbageQueue
+{
+    pub fn new(rules: GarbageRules, seed: u64) -> GarbageQueue
+    {
+        GarbageQueue { rules, attacks: VecDeque::new(), rng: Pcg32::seed_from_u64(seed), hole: None }
+    }
+    pub fn rules(&self) -> &GarbageRules
+    {
+        &self.rules
+    }
+    pub fn set_rules(&mut self, rules: GarbageRules)
+    {
+        self.rules = rules;
+    }
+    pub fn reset(&mut self, seed: u64)
+    {
+        self.attacks.clear();
+        self.rng = Pcg32::seed_from_u64(seed);
+        self.hole = None;
+    }
+    //Rows waiting, ready or not
+    pub fn pending(&self) -> u32
+    {
+        self.attacks.iter().map(|attack| attack.lines).sum()
+    }
+    pub fn push(&mut self, lines: u32)
+    {
+        if lines > 0
+        {
+            self.attacks.push_back(Attack { lines, waited: 0, started: false });
+        }
+    }
+    //Cancels the oldest garbage first, returns the lines left over to send on
+    pub fn cancel(&mut self, mut lines: u32) -> u32
+    {
+        while lines > 0
+        {
+            let attack = match self.attacks.front_mut()
+            {
+                Some(attack) => attack,
+                None => break
+            };
+            let cancelled = attack.lines.min(lines);
+            attack.lines -= cancelled;
+            lines -= cancelled;
+            if attack.lines == 0
+            {
+                self.attacks.pop_front();
+            }
+        }
+        lines
+    }
+    pub fn tick(&mut self)
+    {
+        for attack in self.attacks.iter_mut()
+        {
+            attack.waited = attack.waited.saturating_add(1);
+        }
+    }
+    //Hole columns of the rows that rise now, top row first, at most `cap` of them
+    pub fn take_ready(&mut self, width: u32) -> Vec<u32>
+    {
+        let mut holes = Vec::new();
+        while (holes.len() as u32) < self.rules.cap
+        {
+            let attack = match self.attacks.front_mut()
+            {
+                Some(attack) if attack.waited >= self.rules.delay => attack,
+                _ => break
+            };
+            let rows = attack.lines.min(self.rules.cap - holes.len() as u32);
+            //An attack split over several pieces keeps its column
+            let started = attack.started;
+            attack.started = true;
+            attack.lines -= rows;
+            if attack.lines == 0
+            {
+                self.attacks.pop_front();
+            }
+            if !started
+            {
+                self.hole = Some(self.new_hole(width));
+            }
+            for _i in 0..rows
+            {
+                let hole = self.hole.unwrap_or(0);
+                holes.push(hole);
+                if self.rng.gen_range(0, 100) < self.rules.messiness
+                {
+                    self.hole = Some(self.new_hole(width));
+                }
+            }
+        }
+        holes
+    }
+    //Never the column the last hole was in, so every attack shows up as its own
+    fn new_hole(&mut self, width: u32) -> u32
+    {
+        match self.hole
+        {
+            Some(hole) if width > 1 => (hole + self.rng.gen_range(1, width)) % width,
+            _ => self.rng.gen_range(0, width)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn clear(lines: u32, t_spin: TSpin) -> ClearResult
+    {
+        ClearResult { rows: (0..lines).collect(), t_spin, perfect_clear: false }
+    }
+
+    #[test]
+    fn attack_table()
+    {
+        assert_eq!(0, attack_lines(&clear(1, TSpin::None), 0, false));
+        assert_eq!(4, attack_lines(&clear(4, TSpin::None), 0, false));
+        assert_eq!(5, attack_lines(&clear(4, TSpin::None), 0, true));
+        assert_eq!(4, attack_lines(&clear(2, TSpin::Full), 0, false));
+        assert_eq!(1, attack_lines(&clear(2, TSpin::None), 0, true));
+        assert_eq!(2, attack_lines(&clear(2, TSpin::None), 3, false));
+        assert_eq!(0, attack_lines(&clear(0, TSpin::Full), 5, true));
+        assert_eq!(14, attack_lines(&ClearResult { perfect_clear: true, ..clear(4, TSpin::None) }, 0, false));
+    }
+
+    #[test]
+    fn cancels_oldest_garbage_first()
+    {
+        let mut queue = GarbageQueue::new(GarbageRules::default(), 1);
+        queue.push(2);
+        queue.push(3);
+        assert_eq!(0, queue.cancel(3));
+        assert_eq!(2, queue.pending());
+        assert_eq!(1, queue.cancel(3));
+        assert_eq!(0, queue.pending());
+    }
+
+    #[test]
+    fn garbage_waits_and_respects_the_cap()
+    {
+        let rules = GarbageRules { messiness: 0, delay: 2, cap: 3 };
+        let mut queue = GarbageQueue::new(rules, 7);
+        queue.push(2);
+        queue.push(2);
+        queue.tick();
+        assert!(queue.take_ready(10).is_empty());
+        queue.tick();
+        let holes = queue.take_ready(10);
+        assert_eq!(3, holes.len());
+        //Clean garbage keeps one column per attack, and the next attack moves it
+        assert_eq!(holes[0], holes[1]);
+        assert_ne!(holes[1], holes[2]);
+        let rest = queue.take_ready(10);
+        assert_eq!(vec![holes[2]], rest);
+        assert_eq!(0, queue.pending());
+    }
+
+    #[test]
+    fn messy_garbage_moves_every_row()
+    {
+        let mut queue = GarbageQueue::new(GarbageRules { messiness: 100, delay: 0, cap: 10 }, 3);
+        queue.push(10);
+        let holes = queue.take_ready(10);
+        assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+        assert!(holes.iter().all(|hole| *hole < 10));
+    }
+}
@@ -0,0 +1,27 @@
+pub mod utility;
+pub mod bitboard;
+pub mod board;
+pub mod tetromino;
+pub mod score;
+pub mod randomizer;
+pub mod rules;
+pub mod mode;
+pub mod garbage;
+pub mod state;
+pub mod replay;
+pub mod bot;
+pub mod simulation;
+pub mod net;
+
+pub use bitboard::BitBoard;
+pub use board::{Board, Cell, ClearResult, TSpin};
+pub use tetromino::{Tetromino, TetrominoKind, Rotation, RotationDirection};
+pub use score::Score;
+pub use randomizer::{Randomizer, RandomizerKind, PieceQueue};
+pub use rules::Rules;
+pub use mode::{GameMode, Metric};
+pub use garbage::{GarbageRules, GarbageQueue};
+pub use state::{GameState, GameOver, Inputs};
+pub use replay::{Replay, ReplayPlayer, ReplayError};
+pub use bot::{Bot, BotPlayer, Weights, Move, Placement};
+pub use net::{Lockstep, NetError};
@@ -0,0 +1,165 @@
+use crate::state::GameState;
+use crate::score::Score;
+
+//A stat the HUD shows for a mode, formatting is left to whoever draws it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric
+{
+    Score,
+    Level,
+    Lines,
+    LinesLeft,
+    Time,
+    TimeLeft,
+    PiecesPerSecond,
+    LinesSent
+}
+
+//Goal and end conditions of a game, the lines counted are the ones `Board::clear_lines` reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameMode
+{
+    //Plays until top out, used by the bot tools
+    #[default]
+    Endless,
+    //Clear 40 lines as fast as possible
+    Sprint,
+    //As many points as possible in two minutes
+    Ultra,
+    //Clear 150 lines, through level 15
+    Marathon,
+    //No goal and no top out, a full board is emptied instead
+    Zen,
+    //Against another player, last one standing wins
+    Versus
+}
+impl GameMode
+{
+    pub const ALL: [GameMode; 6] = [GameMode::Endless, GameMode::Sprint, GameMode::Ultra, GameMode::Marathon, GameMode::Zen, GameMode::Versus];
+    //The modes a player picks from on their own, `Endless` and `Versus` are left out
+    pub const SELECTABLE: [GameMode; 4] = [GameMode::Sprint, GameMode::Ultra, GameMode::Marathon, GameMode::Zen];
+    //The modes that can end in a high score, `Zen` never ends and `Versus` has a winner instead
+    pub const RANKED: [GameMode; 4] = [GameMode::Endless, GameMode::Sprint, GameMode::Ultra, GameMode::Marathon];
+    const SPRINT_LINES: u32 = 40;
+    const ULTRA_SECONDS: u64 = 120;
+    const MARATHON_LEVELS: u32 = 15;
+
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            GameMode::Endless => "Endless",
+            GameMode::Sprint => "Sprint",
+            GameMode::Ultra => "Ultra",
+            GameMode::Marathon => "Marathon",
+            GameMode::Zen => "Zen",
+            GameMode::Versus => "Versus"
+        }
+    }
+    //Case insensitive
+    pub fn from_name(name: &str) -> Option<GameMode>
+    {
+        GameMode::ALL.iter().copied().find(|mode| mode.name().eq_ignore_ascii_case(name))
+    }
+    pub fn line_goal(self) -> Option<u32>
+    {
+        match self
+        {
+            GameMode::Sprint => Some(GameMode::SPRINT_LINES),
+            GameMode::Marathon => Some(GameMode::MARATHON_LEVELS * Score::LINES_PER_LEVEL),
+            _ => None
+        }
+    }
+    //In logical frames
+    pub fn time_limit(self) -> Option<u64>
+    {
+        match self
+        {
+            GameMode::Ultra => Some(GameMode::ULTRA_SECONDS * GameState::FRAME_RATE as u64),
+            _ => None
+        }
+    }
+    pub fn tops_out(self) -> bool
+    {
+        self != GameMode::Zen
+    }
+    pub fn metrics(self) -> &'static [Metric]
+    {
+        match self
+        {
+            GameMode::Endless | GameMode::Marathon => &[Metric::Score, Metric::Level, Metric::Lines, Metric::Time, Metric::PiecesPerSecond],
+            GameMode::Sprint => &[Metric::LinesLeft, Metric::Time, Metric::PiecesPerSecond],
+            GameMode::Ultra => &[Metric::TimeLeft, Metric::Score, Metric::Lines, Metric::PiecesPerSecond],
+            GameMode::Zen => &[Metric::Score, Metric::Lines, Metric::Time, Metric::PiecesPerSecond],
+            GameMode::Versus => &[Metric::LinesSent, Metric::Lines, Metric::Time, Metric::PiecesPerSecond]
+        }
+    }
+    pub fn is_complete(self, state: &GameState) -> bool
+    {
+        self.lines_left(state) == Some(0) || self.time_left(state) == Some(0.0)
+    }
+    pub fn lines_left(self, state: &GameState) -> Option<u32>
+    {
+        self.line_goal().map(|goal| goal.saturating_sub(state.score().lines()))
+    }
+    //In seconds
+    pub fn time_left(self, state: &GameState) -> Option<f64>
+    {
+        self.time_limit().map(|limit| limit.saturating_sub(state.frame()) as f64 * GameState::FRAME_TIME)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::state::{GameOver, Inputs};
+    use crate::rules::Rules;
+
+    #[test]
+    fn ultra_ends_when_time_runs_out()
+    {
+        let mut state = GameState::with_seed(1);
+        state.set_mode(GameMode::Ultra);
+        state.set_rules(Rules { lock_delay: 600, ..Rules::default() });
+        for _i in 0..GameMode::ULTRA_SECONDS * GameState::FRAME_RATE as u64 - 1
+        {
+            state.step_frame(&Inputs::default());
+        }
+        assert!(!state.is_game_over());
+        assert_eq!(Some(GameState::FRAME_TIME), GameMode::Ultra.time_left(&state));
+        state.step_frame(&Inputs::default());
+        assert_eq!(Some(GameOver::Completed), state.game_over());
+    }
+
+    #[test]
+    fn zen_never_tops_out()
+    {
+        let mut state = GameState::with_seed(2);
+        state.set_mode(GameMode::Zen);
+        let hard_drop = Inputs { hard_drop: true, ..Inputs::default() };
+        for _i in 0..200
+        {
+            state.step_frame(&hard_drop);
+        }
+        assert!(!state.is_game_over());
+        assert_eq!(200, state.pieces_placed());
+
+        state.set_mode(GameMode::Endless);
+        for _i in 0..200
+        {
+            state.step_frame(&hard_drop);
+        }
+        assert!(state.game_over().is_some_and(GameOver::is_top_out));
+    }
+
+    #[test]
+    fn names_and_goals()
+    {
+        assert_eq!(Some(GameMode::Sprint), GameMode::from_name("sprint"));
+        assert_eq!(None, GameMode::from_name("blitz"));
+        assert_eq!(Some(40), GameMode::Sprint.lines_left(&GameState::new()));
+        assert_eq!(Some(150), GameMode::Marathon.line_goal());
+        assert_eq!(None, GameMode::Zen.lines_left(&GameState::new()));
+    }
+}
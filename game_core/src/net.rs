@@ -0,0 +1,651 @@
+use std::{fmt, io};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use crate::rules::Rules;
+use crate::garbage::GarbageRules;
+use crate::mode::GameMode;
+use crate::state::{GameState, Inputs};
+
+#[derive(Debug)]
+pub enum NetError
+{
+    Io(io::Error),
+    Disconnected,
+    //The peer sent something this version doesn't understand
+    Protocol(String),
+    VersionMismatch(u32),
+    //The two simulations disagreed about the given frame
+    Desync(u64)
+}
+impl fmt::Display for NetError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            NetError::Io(e) => write!(f, "network error: {}", e),
+            NetError::Disconnected => write!(f, "the other player disconnected"),
+            NetError::Protocol(e) => write!(f, "protocol error: {}", e),
+            NetError::VersionMismatch(version) => write!(f, "the other player uses protocol version {}, this is {}", version, PROTOCOL_VERSION),
+            NetError::Desync(frame) => write!(f, "the games went out of sync at frame {}", frame)
+        }
+    }
+}
+impl std::error::Error for NetError {}
+impl From<io::Error> for NetError
+{
+    fn from(e: io::Error) -> NetError
+    {
+        NetError::Io(e)
+    }
+}
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+//What the host decides for both players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Start
+{
+    pub seed: u64,
+    pub rules: Rules,
+    pub garbage: GarbageRules
+}
+
+//Every message is a big endian u32 length followed by a tag byte and the fields in order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Message
+{
+    Hello { version: u32 },
+    Start(Start),
+    //The sender's inputs for a frame, sent in frame order
+    Input { frame: u64, inputs: Inputs },
+    //Lines the sender's board sent on a frame, checked against the receiver's copy of that board
+    Garbage { frame: u64, lines: u32 },
+    //Checksum of both games after a frame
+    Hash { frame: u64, hash: u64 },
+    Quit
+}
+impl Message
+{
+    //Larger lengths can only come from a broken or foreign peer
+    const MAX_LENGTH: usize = 256;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let mut bytes = vec![0; 4];
+        match self
+        {
+            Message::Hello { version } =>
+            {
+                bytes.push(0);
+                bytes.extend_from_slice(&version.to_be_bytes());
+            }
+            Message::Start(start) =>
+            {
+                bytes.push(1);
+                bytes.extend_from_slice(&start.seed.to_be_bytes());
+                let rules = &start.rules;
+                let garbage = &start.garbage;
+                for value in [rules.lock_delay, rules.max_lock_resets, rules.das, rules.arr, rules.width, rules.height, rules.buffer, garbage.messiness, garbage.delay, garbage.cap].iter()
+                {
+                    bytes.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            Message::Input { frame, inputs } =>
+            {
+                bytes.push(2);
+                bytes.extend_from_slice(&frame.to_be_bytes());
+                bytes.push(inputs.horizontal.signum() as i8 as u8);
+                let flags = [inputs.soft_drop, inputs.rotate_cw, inputs.rotate_ccw, inputs.rotate_180, inputs.hard_drop, inputs.hold];
+                bytes.push(flags.iter().enumerate().fold(0, |byte, (i, flag)| byte | (*flag as u8) << i));
+            }
+            Message::Garbage { frame, lines } =>
+            {
+                bytes.push(3);
+                bytes.extend_from_slice(&frame.to_be_bytes());
+                bytes.extend_from_slice(&lines.to_be_bytes());
+            }
+            Message::Hash { frame, hash } =>
+            {
+                bytes.push(4);
+                bytes.extend_from_slice(&frame.to_be_bytes());
+                bytes.extend_from_slice(&hash.to_be_bytes());
+            }
+            Message::Quit => bytes.push(5)
+        }
+        let length = (bytes.len() - 4) as u32;
+        bytes[..4].copy_from_slice(&length.to_be_bytes());
+        bytes
+    }
+    //The first whole message in `bytes` and how many bytes it used, None if more are needed
+    pub fn decode(bytes: &[u8]) -> Result<Option<(Message, usize)>, NetError>
+    {
+        if bytes.len() < 4
+        {
+            return Ok(None);
+        }
+        let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        if length == 0 || length > Message::MAX_LENGTH
+        {
+            return Err(NetError::Protocol(format!("bad message length {}", length)));
+        }
+        if bytes.len() < 4 + length
+        {
+            return Ok(None);
+        }
+        let mut reader = Reader { bytes: &bytes[5..4 + length] };
+        let message = match bytes[4]
+        {
+            0 => Message::Hello { version: reader.u32()? },
+            1 =>
+            {
+                let seed = reader.u64()?;
+                let mut values = [0; 10];
+                for value in values.iter_mut()
+                {
+                    *value = reader.u32()?;
+                }
+                let rules = Rules { lock_delay: values[0], max_lock_resets: values[1], das: values[2], arr: values[3], width: values[4], height: values[5], buffer: values[6] };
+                let garbage = GarbageRules { messiness: values[7], delay: values[8], cap: values[9] };
+                //A board the settings couldn't make would only crash or stall the game
+                rules.check().and_then(|_| garbage.check()).map_err(NetError::Protocol)?;
+                Message::Start(Start { seed, rules, garbage })
+            }
+            2 =>
+            {
+                let frame = reader.u64()?;
+                let horizontal = reader.u8()? as i8 as i32;
+                //Shifts only check the cell they end on, anything further would pass through the stack
+                if !(-1..=1).contains(&horizontal)
+                {
+                    return Err(NetError::Protocol(format!("horizontal input {}", horizontal)));
+                }
+                let flags = reader.u8()?;
+                let flag = |i: u32| flags & (1 << i) != 0;
+                let inputs = Inputs { horizontal, soft_drop: flag(0), rotate_cw: flag(1), rotate_ccw: flag(2), rotate_180: flag(3), hard_drop: flag(4), hold: flag(5) };
+                Message::Input { frame, inputs }
+            }
+            3 => Message::Garbage { frame: reader.u64()?, lines: reader.u32()? },
+            4 => Message::Hash { frame: reader.u64()?, hash: reader.u64()? },
+            5 => Message::Quit,
+            tag => return Err(NetError::Protocol(format!("unknown message {}", tag)))
+        };
+        if !reader.bytes.is_empty()
+        {
+            return Err(NetError::Protocol("message longer than its fields".to_string()));
+        }
+        Ok(Some((message, 4 + length)))
+    }
+}
+
+struct Reader<'a>
+{
+    bytes: &'a [u8]
+}
+impl<'a> Reader<'a>
+{
+    fn take(&mut self, count: usize) -> Result<&'a [u8], NetError>
+    {
+        if self.bytes.len() < count
+        {
+            return Err(NetError::Protocol("message shorter than its fields".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+    fn u8(&mut self) -> Result<u8, NetError>
+    {
+        Ok(self.take(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, NetError>
+    {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+    fn u64(&mut self) -> Result<u64, NetError>
+    {
+        let mut value = [0; 8];
+        value.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(value))
+    }
+}
+
+//Messages over a TCP stream. Writes block, reads can poll without waiting
+pub struct Connection
+{
+    stream: TcpStream,
+    buffer: Vec<u8>
+}
+impl Connection
+{
+    pub fn new(stream: TcpStream) -> Result<Connection, NetError>
+    {
+        //Inputs are tiny and late ones stall both players
+        stream.set_nodelay(true)?;
+        //Streams accepted from a polling listener can inherit its non-blocking mode
+        stream.set_nonblocking(false)?;
+        Ok(Connection { stream, buffer: Vec::new() })
+    }
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError>
+    {
+        match self.stream.write_all(&message.encode())
+        {
+            Ok(()) => Ok(()),
+            Err(e) if Connection::is_disconnect(&e) => Err(NetError::Disconnected),
+            Err(e) => Err(NetError::Io(e))
+        }
+    }
+    fn is_disconnect(e: &io::Error) -> bool
+    {
+        matches!(e.kind(), io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted)
+    }
+    //The next message, waiting for one if `block` is set
+    pub fn receive(&mut self, block: bool) -> Result<Option<Message>, NetError>
+    {
+        loop
+        {
+            if let Some((message, used)) = Message::decode(&self.buffer)?
+            {
+                self.buffer.drain(..used);
+                return Ok(Some(message));
+            }
+            let mut chunk = [0; 512];
+            self.stream.set_nonblocking(!block)?;
+            let read = self.stream.read(&mut chunk);
+            self.stream.set_nonblocking(false)?;
+            match read
+            {
+                Ok(0) => return Err(NetError::Disconnected),
+                Ok(count) => self.buffer.extend_from_slice(&chunk[..count]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) if Connection::is_disconnect(&e) => return Err(NetError::Disconnected),
+                Err(e) => return Err(NetError::Io(e))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Check
+{
+    Garbage(u64),
+    Hash(u64)
+}
+
+//Values both peers work out on their own, a key seen on both sides with different values is a desync
+#[derive(Debug, Default)]
+struct Checks
+{
+    local: HashMap<Check, u64>,
+    remote: HashMap<Check, u64>
+}
+impl Checks
+{
+    fn add(check: Check, value: u64, own: &mut HashMap<Check, u64>, other: &mut HashMap<Check, u64>) -> Result<(), NetError>
+    {
+        match other.remove(&check)
+        {
+            Some(other) if other == value => Ok(()),
+            Some(_) => Err(NetError::Desync(match check { Check::Garbage(frame) | Check::Hash(frame) => frame })),
+            None =>
+            {
+                own.insert(check, value);
+                Ok(())
+            }
+        }
+    }
+    fn local(&mut self, check: Check, value: u64) -> Result<(), NetError>
+    {
+        Checks::add(check, value, &mut self.local, &mut self.remote)
+    }
+    fn remote(&mut self, check: Check, value: u64) -> Result<(), NetError>
+    {
+        Checks::add(check, value, &mut self.remote, &mut self.local)
+    }
+}
+
+//Both peers simulate both games and only ever step a frame once they have both players' inputs for it.
+//Local inputs are scheduled `INPUT_DELAY` frames ahead so the peer's usually arrive in time
+pub struct Lockstep
+{
+    connection: Connection,
+    local: usize,
+    start: Start,
+    //Next frame to simulate
+    frame: u64,
+    local_inputs: VecDeque<Inputs>,
+    remote_inputs: VecDeque<Inputs>,
+    //Frame the peer's next input is for
+    remote_frame: u64,
+    checks: Checks,
+    //Presses that haven't had a frame of their own yet
+    pending: Inputs,
+    accumulator: f64,
+    quit: bool
+}
+impl Lockstep
+{
+    pub const INPUT_DELAY: usize = 3;
+    //Frames between checksums
+    pub const HASH_INTERVAL: u64 = 60;
+    pub const DEFAULT_PORT: u16 = 7878;
+
+    //Blocks until a player connects, the host is player 1 and picks the seed and rules
+    pub fn host(listener: &TcpListener, start: Start) -> Result<Lockstep, NetError>
+    {
+        let (stream, _) = listener.accept()?;
+        Lockstep::accept(stream, start)
+    }
+    //Like `host` with a player that already connected, for hosts that poll their listener
+    pub fn accept(stream: TcpStream, start: Start) -> Result<Lockstep, NetError>
+    {
+        let mut connection = Connection::new(stream)?;
+        connection.send(&Message::Hello { version: PROTOCOL_VERSION })?;
+        Lockstep::expect_hello(&mut connection)?;
+        connection.send(&Message::Start(start))?;
+        Ok(Lockstep::new(connection, 0, start))
+    }
+    //Player 2, takes the seed and rules from the host
+    pub fn join<A: ToSocketAddrs>(address: A) -> Result<Lockstep, NetError>
+    {
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        connection.send(&Message::Hello { version: PROTOCOL_VERSION })?;
+        Lockstep::expect_hello(&mut connection)?;
+        match connection.receive(true)?
+        {
+            Some(Message::Start(start)) => Ok(Lockstep::new(connection, 1, start)),
+            other => Err(NetError::Protocol(format!("expected the game settings, got {:?}", other)))
+        }
+    }
+    fn expect_hello(connection: &mut Connection) -> Result<(), NetError>
+    {
+        match connection.receive(true)?
+        {
+            Some(Message::Hello { version }) if version == PROTOCOL_VERSION => Ok(()),
+            Some(Message::Hello { version }) => Err(NetError::VersionMismatch(version)),
+            other => Err(NetError::Protocol(format!("expected a greeting, got {:?}", other)))
+        }
+    }
+    fn new(connection: Connection, local: usize, start: Start) -> Lockstep
+    {
+        let delay: VecDeque<Inputs> = (0..Lockstep::INPUT_DELAY).map(|_| Inputs::default()).collect();
+        Lockstep
+        {
+            connection,
+            local,
+            start,
+            frame: 0,
+            local_inputs: delay.clone(),
+            remote_inputs: delay,
+            remote_frame: Lockstep::INPUT_DELAY as u64,
+            checks: Checks::default(),
+            pending: Inputs::default(),
+            accumulator: 0.0,
+            quit: false
+        }
+    }
+    //Index of this side's game, 0 for the host
+    pub fn local_player(&self) -> usize
+    {
+        self.local
+    }
+    pub fn start(&self) -> &Start
+    {
+        &self.start
+    }
+    //Both games as the host set them up, in player order
+    pub fn create_states(&self) -> [GameState; 2]
+    {
+        let create = ||
+        {
+            let mut state = GameState::with_seed(self.start.seed);
+            state.set_rules(self.start.rules);
+            state.set_mode(GameMode::Versus);
+            state.set_garbage_rules(self.start.garbage);
+            state
+        };
+        [create(), create()]
+    }
+    //Of both games, in player order
+    pub fn checksum(first: &GameState, second: &GameState) -> u64
+    {
+        first.checksum() ^ second.checksum().rotate_left(32)
+    }
+    //The peer left and every frame it played has been simulated
+    pub fn has_ended(&self) -> bool
+    {
+        self.quit && self.remote_inputs.is_empty()
+    }
+    //Only so far ahead of the peer, past that the local player waits
+    pub fn can_send(&self) -> bool
+    {
+        self.local_inputs.len() < Lockstep::INPUT_DELAY * 2
+    }
+    //Schedules the local player's inputs for the next free frame
+    pub fn send_inputs(&mut self, inputs: Inputs) -> Result<(), NetError>
+    {
+        let frame = self.frame + self.local_inputs.len() as u64;
+        self.send(&Message::Input { frame, inputs })?;
+        self.local_inputs.push_back(inputs);
+        Ok(())
+    }
+    pub fn send_quit(&mut self) -> Result<(), NetError>
+    {
+        self.send(&Message::Quit)
+    }
+    //Nobody reads what is sent to a peer that is gone, but its last frames are still waiting to be polled
+    fn send(&mut self, message: &Message) -> Result<(), NetError>
+    {
+        match self.connection.send(message)
+        {
+            Err(NetError::Disconnected) => Ok(()),
+            result => result
+        }
+    }
+    //Real time version of `send_inputs` and `advance` for a player at the keyboard, presses are kept until a frame takes them like `GameState::step`
+    pub fn step(&mut self, delta_time: f32, inputs: &Inputs, states: [&mut GameState; 2]) -> Result<(), NetError>
+    {
+        //A stalled peer shouldn't leave a burst of frames to catch up on
+        self.accumulator = (self.accumulator + delta_time as f64).min(GameState::FRAME_TIME * Lockstep::INPUT_DELAY as f64);
+        self.pending = Inputs
+        {
+            horizontal: inputs.horizontal,
+            soft_drop: inputs.soft_drop,
+            rotate_cw: self.pending.rotate_cw || inputs.rotate_cw,
+            rotate_ccw: self.pending.rotate_ccw || inputs.rotate_ccw,
+            rotate_180: self.pending.rotate_180 || inputs.rotate_180,
+            hard_drop: self.pending.hard_drop || inputs.hard_drop,
+            hold: self.pending.hold || inputs.hold
+        };
+        while self.accumulator + 1e-9 >= GameState::FRAME_TIME && self.can_send()
+        {
+            self.accumulator -= GameState::FRAME_TIME;
+            let inputs = self.pending;
+            self.send_inputs(inputs)?;
+            self.pending = Inputs { horizontal: inputs.horizontal, soft_drop: inputs.soft_drop, ..Inputs::default() };
+        }
+        self.advance(states)?;
+        Ok(())
+    }
+    //Reads what the peer sent and simulates every frame both inputs are known for, returns how many
+    pub fn advance(&mut self, mut states: [&mut GameState; 2]) -> Result<u32, NetError>
+    {
+        self.poll()?;
+        let mut frames = 0;
+        while !self.local_inputs.is_empty() && !self.remote_inputs.is_empty()
+        {
+            let local = self.local_inputs.pop_front().unwrap_or_default();
+            let remote = self.remote_inputs.pop_front().unwrap_or_default();
+            let inputs = if self.local == 0 { [local, remote] } else { [remote, local] };
+            for (state, inputs) in states.iter_mut().zip(inputs.iter())
+            {
+                state.step_frame(inputs);
+            }
+            //Garbage changes hands between frames, the same way on both sides
+            let sent = [states[0].take_outgoing(), states[1].take_outgoing()];
+            states[0].receive_garbage(sent[1]);
+            states[1].receive_garbage(sent[0]);
+
+            let frame = self.frame;
+            self.frame += 1;
+            frames += 1;
+            if sent[self.local] > 0
+            {
+                self.send(&Message::Garbage { frame, lines: sent[self.local] })?;
+            }
+            if sent[1 - self.local] > 0
+            {
+                self.checks.local(Check::Garbage(frame), sent[1 - self.local] as u64)?;
+            }
+            if frame.is_multiple_of(Lockstep::HASH_INTERVAL)
+            {
+                let hash = Lockstep::checksum(states[0], states[1]);
+                self.send(&Message::Hash { frame, hash })?;
+                self.checks.local(Check::Hash(frame), hash)?;
+            }
+        }
+        Ok(frames)
+    }
+    fn poll(&mut self) -> Result<(), NetError>
+    {
+        loop
+        {
+            let message = match self.connection.receive(false)
+            {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(NetError::Disconnected) =>
+                {
+                    self.quit = true;
+                    break;
+                }
+                Err(e) => return Err(e)
+            };
+            match message
+            {
+                Message::Input { frame, inputs } if frame == self.remote_frame =>
+                {
+                    self.remote_inputs.push_back(inputs);
+                    self.remote_frame += 1;
+                }
+                Message::Input { frame, .. } => return Err(NetError::Protocol(format!("expected inputs for frame {}, got {}", self.remote_frame, frame))),
+                Message::Garbage { frame, lines } => self.checks.remote(Check::Garbage(frame), lines as u64)?,
+                Message::Hash { frame, hash } => self.checks.remote(Check::Hash(frame), hash)?,
+                Message::Quit =>
+                {
+                    self.quit = true;
+                    break;
+                }
+                other => return Err(NetError::Protocol(format!("unexpected {:?}", other)))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::thread;
+    use crate::bot::{Bot, BotPlayer};
+
+    #[test]
+    fn messages_round_trip_in_pieces()
+    {
+        let messages = [
+            Message::Hello { version: PROTOCOL_VERSION },
+            Message::Start(Start { seed: u64::MAX - 3, rules: Rules { width: 12, ..Rules::default() }, garbage: GarbageRules { messiness: 40, delay: 5, cap: 6 } }),
+            Message::Input { frame: 9, inputs: Inputs { horizontal: -1, rotate_180: true, hold: true, ..Inputs::default() } },
+            Message::Garbage { frame: 10, lines: 4 },
+            Message::Hash { frame: 60, hash: 0x0123_4567_89ab_cdef },
+            Message::Quit
+        ];
+        let bytes: Vec<u8> = messages.iter().flat_map(|message| message.encode()).collect();
+        //Any split point only ever gives whole messages
+        let mut decoded = Vec::new();
+        let mut buffer = Vec::new();
+        for byte in bytes.iter()
+        {
+            buffer.push(*byte);
+            while let Some((message, used)) = Message::decode(&buffer).unwrap()
+            {
+                decoded.push(message);
+                buffer.drain(..used);
+            }
+        }
+        assert_eq!(messages.to_vec(), decoded);
+        assert!(buffer.is_empty());
+        assert!(matches!(Message::decode(&[0, 0, 0, 1, 9]), Err(NetError::Protocol(_))));
+        assert!(matches!(Message::decode(&[0, 0, 1, 1]), Err(NetError::Protocol(_))));
+    }
+
+    #[test]
+    fn refuses_what_the_game_cant_play()
+    {
+        let start = |rules: Rules, garbage: GarbageRules| Message::decode(&Message::Start(Start { seed: 1, rules, garbage }).encode());
+        assert!(start(Rules::default(), GarbageRules::default()).unwrap().is_some());
+        assert!(matches!(start(Rules { width: 0, ..Rules::default() }, GarbageRules::default()), Err(NetError::Protocol(_))));
+        assert!(matches!(start(Rules { width: 33, ..Rules::default() }, GarbageRules::default()), Err(NetError::Protocol(_))));
+        assert!(matches!(start(Rules { height: u32::MAX, ..Rules::default() }, GarbageRules::default()), Err(NetError::Protocol(_))));
+        assert!(matches!(start(Rules { buffer: 1000, ..Rules::default() }, GarbageRules::default()), Err(NetError::Protocol(_))));
+        assert!(matches!(start(Rules::default(), GarbageRules { cap: 0, ..GarbageRules::default() }), Err(NetError::Protocol(_))));
+
+        let mut bytes = Message::Input { frame: 0, inputs: Inputs::default() }.encode();
+        for (horizontal, valid) in [(1, true), (0xff, true), (5, false), (0x80, false)].iter()
+        {
+            bytes[13] = *horizontal;
+            assert_eq!(*valid, Message::decode(&bytes).is_ok());
+        }
+    }
+
+    #[test]
+    fn mismatched_checks_are_a_desync()
+    {
+        let mut checks = Checks::default();
+        checks.local(Check::Hash(60), 1).unwrap();
+        checks.remote(Check::Hash(60), 1).unwrap();
+        checks.remote(Check::Garbage(70), 2).unwrap();
+        assert!(matches!(checks.local(Check::Garbage(70), 3), Err(NetError::Desync(70))));
+        assert!(checks.local.is_empty() && checks.remote.is_empty());
+    }
+
+    //Each side plays its own board with a bot and mirrors the other, both must end up with the same games
+    fn play(mut session: Lockstep, frames: u64) -> u64
+    {
+        let mut states = session.create_states();
+        let mut bot = BotPlayer::new(Bot::default());
+        let local = session.local_player();
+        while session.frame < frames && !states.iter().any(GameState::is_game_over)
+        {
+            if session.can_send()
+            {
+                let inputs = bot.next_inputs(&states[local]);
+                session.send_inputs(inputs).unwrap();
+            }
+            let [first, second] = &mut states;
+            if session.advance([first, second]).unwrap() == 0
+            {
+                thread::yield_now();
+            }
+        }
+        Lockstep::checksum(&states[0], &states[1])
+    }
+
+    #[test]
+    fn peers_on_loopback_stay_in_sync()
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let start = Start { seed: 11, rules: Rules::default(), garbage: GarbageRules::default() };
+        let host = thread::spawn(move || play(Lockstep::host(&listener, start).unwrap(), 600));
+        let client = Lockstep::join(address).unwrap();
+        assert_eq!(start, *client.start());
+        assert_eq!(1, client.local_player());
+        let client = play(client, 600);
+        assert_eq!(host.join().unwrap(), client);
+    }
+}
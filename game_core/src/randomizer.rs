@@ -0,0 +1,263 @@
+use std::collections::VecDeque;
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg32;
+use crate::tetromino::TetrominoKind;
+
+const ALL_KINDS: [TetrominoKind; 7] =
+[
+    TetrominoKind::I,
+    TetrominoKind::J,
+    TetrominoKind::L,
+    TetrominoKind::O,
+    TetrominoKind::S,
+    TetrominoKind::T,
+    TetrominoKind::Z
+];
+
+pub trait Randomizer
+{
+    fn next(&mut self) -> TetrominoKind;
+    //Restarts the sequence, the same seed always gives the same sequence
+    fn reset(&mut self, seed: u64);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomizerKind
+{
+    Bag7,
+    Bag14,
+    Random,
+    History
+}
+impl RandomizerKind
+{
+    pub fn create(self, seed: u64) -> Box<dyn Randomizer>
+    {
+        match self
+        {
+            RandomizerKind::Bag7 => Box::new(BagRandomizer::new(seed, 1)),
+            RandomizerKind::Bag14 => Box::new(BagRandomizer::new(seed, 2)),
+            RandomizerKind::Random => Box::new(PureRandomizer::new(seed)),
+            RandomizerKind::History => Box::new(HistoryRandomizer::new(seed, 4))
+        }
+    }
+}
+
+//Deals every piece `copies` times in a shuffled bag before refilling
+pub struct BagRandomizer
+{
+    rng: Pcg32,
+    copies: usize,
+    bag: Vec<TetrominoKind>
+}
+impl BagRandomizer
+{
+    pub fn new(seed: u64, copies: usize) -> BagRandomizer
+    {
+        BagRandomizer { rng: Pcg32::seed_from_u64(seed), copies, bag: Vec::new() }
+    }
+}
+impl Randomizer for BagRandomizer
+{
+    fn next(&mut self) -> TetrominoKind
+    {
+        if self.bag.is_empty()
+        {
+            for _i in 0..self.copies
+            {
+                self.bag.extend_from_slice(&ALL_KINDS);
+            }
+            self.bag.shuffle(&mut self.rng);
+        }
+        self.bag.pop().unwrap()
+    }
+    fn reset(&mut self, seed: u64)
+    {
+        self.rng = Pcg32::seed_from_u64(seed);
+        self.bag.clear();
+    }
+}
+
+pub struct PureRandomizer
+{
+    rng: Pcg32
+}
+impl PureRandomizer
+{
+    pub fn new(seed: u64) -> PureRandomizer
+    {
+        PureRandomizer { rng: Pcg32::seed_from_u64(seed) }
+    }
+}
+impl Randomizer for PureRandomizer
+{
+    fn next(&mut self) -> TetrominoKind
+    {
+        *ALL_KINDS.choose(&mut self.rng).unwrap()
+    }
+    fn reset(&mut self, seed: u64)
+    {
+        self.rng = Pcg32::seed_from_u64(seed);
+    }
+}
+
+//TGM style, rerolls up to `rolls` times when the piece is in the recent history
+pub struct HistoryRandomizer
+{
+    rng: Pcg32,
+    rolls: usize,
+    history: VecDeque<TetrominoKind>,
+    first: bool
+}
+impl HistoryRandomizer
+{
+    const HISTORY_SIZE: usize = 4;
+    const FIRST_PIECES: [TetrominoKind; 4] = [TetrominoKind::I, TetrominoKind::J, TetrominoKind::L, TetrominoKind::T];
+
+    pub fn new(seed: u64, rolls: usize) -> HistoryRandomizer
+    {
+        let mut randomizer = HistoryRandomizer { rng: Pcg32::seed_from_u64(seed), rolls, history: VecDeque::new(), first: true };
+        randomizer.reset(seed);
+        randomizer
+    }
+}
+impl Randomizer for HistoryRandomizer
+{
+    fn next(&mut self) -> TetrominoKind
+    {
+        let piece = if self.first
+        {
+            self.first = false;
+            *HistoryRandomizer::FIRST_PIECES.choose(&mut self.rng).unwrap()
+        }
+        else
+        {
+            let mut piece = ALL_KINDS[self.rng.gen_range(0, ALL_KINDS.len())];
+            for _i in 1..self.rolls
+            {
+                if !self.history.contains(&piece)
+                {
+                    break;
+                }
+                piece = ALL_KINDS[self.rng.gen_range(0, ALL_KINDS.len())];
+            }
+            piece
+        };
+        self.history.pop_front();
+        self.history.push_back(piece);
+        piece
+    }
+    fn reset(&mut self, seed: u64)
+    {
+        self.rng = Pcg32::seed_from_u64(seed);
+        self.history = vec![TetrominoKind::Z; HistoryRandomizer::HISTORY_SIZE].into();
+        self.first = true;
+    }
+}
+
+//Keeps enough upcoming pieces buffered for the preview
+pub struct PieceQueue
+{
+    randomizer: Box<dyn Randomizer>,
+    queue: VecDeque<TetrominoKind>,
+    preview_size: usize
+}
+impl PieceQueue
+{
+    pub fn new(randomizer: Box<dyn Randomizer>, preview_size: usize) -> PieceQueue
+    {
+        let mut queue = PieceQueue { randomizer, queue: VecDeque::new(), preview_size };
+        queue.fill(preview_size);
+        queue
+    }
+    fn fill(&mut self, count: usize)
+    {
+        while self.queue.len() < count
+        {
+            let piece = self.randomizer.next();
+            self.queue.push_back(piece);
+        }
+    }
+    pub fn pop(&mut self) -> TetrominoKind
+    {
+        self.fill(self.preview_size + 1);
+        let piece = self.queue.pop_front().unwrap();
+        self.fill(self.preview_size);
+        piece
+    }
+    //The buffered preview, always `preview_size` long
+    pub fn preview(&self) -> impl Iterator<Item = &TetrominoKind>
+    {
+        self.queue.iter().take(self.preview_size)
+    }
+    //The next `count` pieces, generating more if the buffer is too short
+    pub fn peek(&mut self, count: usize) -> Vec<TetrominoKind>
+    {
+        self.fill(count);
+        self.queue.iter().take(count).cloned().collect()
+    }
+    pub fn reset(&mut self, seed: u64)
+    {
+        self.randomizer.reset(seed);
+        self.queue.clear();
+        self.fill(self.preview_size);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn sequence(kind: RandomizerKind, seed: u64, count: usize) -> Vec<TetrominoKind>
+    {
+        let mut randomizer = kind.create(seed);
+        (0..count).map(|_| randomizer.next()).collect()
+    }
+
+    #[test]
+    fn same_seed_same_sequence()
+    {
+        for kind in [RandomizerKind::Bag7, RandomizerKind::Bag14, RandomizerKind::Random, RandomizerKind::History].iter()
+        {
+            assert_eq!(sequence(*kind, 42, 100), sequence(*kind, 42, 100));
+            assert_ne!(sequence(*kind, 42, 100), sequence(*kind, 43, 100));
+
+            let mut randomizer = kind.create(42);
+            randomizer.next();
+            randomizer.reset(42);
+            assert_eq!(sequence(*kind, 42, 10), (0..10).map(|_| randomizer.next()).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn bags_contain_every_piece()
+    {
+        for (kind, size) in [(RandomizerKind::Bag7, 7), (RandomizerKind::Bag14, 14)].iter()
+        {
+            let pieces = sequence(*kind, 7, size * 10);
+            for bag in pieces.chunks(*size)
+            {
+                for piece in ALL_KINDS.iter()
+                {
+                    assert_eq!(size / 7, bag.iter().filter(|kind| *kind == piece).count());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn preview_matches_dealt_pieces_across_bags()
+    {
+        let mut queue = PieceQueue::new(RandomizerKind::Bag7.create(3), 5);
+        let expected = sequence(RandomizerKind::Bag7, 3, 40);
+
+        assert_eq!(expected[..12].to_vec(), queue.peek(12));
+        for i in 0..30
+        {
+            assert_eq!(expected[i..i + 5].to_vec(), queue.preview().cloned().collect::<Vec<_>>());
+            assert_eq!(expected[i], queue.pop());
+        }
+    }
+}
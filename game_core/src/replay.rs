@@ -0,0 +1,350 @@
+use std::{fmt, fs, io};
+use std::path::Path;
+use crate::rules::Rules;
+use crate::mode::GameMode;
+use crate::state::{GameState, Inputs};
+
+#[derive(Debug)]
+pub enum ReplayError
+{
+    Io(io::Error),
+    UnsupportedVersion(String),
+    Malformed(usize, String)
+}
+impl fmt::Display for ReplayError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            ReplayError::Io(e) => write!(f, "could not read replay: {}", e),
+            ReplayError::UnsupportedVersion(header) => write!(f, "unsupported replay version '{}'", header),
+            ReplayError::Malformed(line, text) => write!(f, "malformed replay at line {}: '{}'", line, text)
+        }
+    }
+}
+impl std::error::Error for ReplayError {}
+impl From<io::Error> for ReplayError
+{
+    fn from(e: io::Error) -> ReplayError
+    {
+        ReplayError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayFrame
+{
+    pub delta_time: f32,
+    pub inputs: Inputs
+}
+
+//Seed, rules and every simulation step, enough to reproduce a game exactly
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay
+{
+    pub seed: u64,
+    pub rules: Rules,
+    pub mode: GameMode,
+    pub frames: Vec<ReplayFrame>
+}
+impl Replay
+{
+    const HEADER: &'static str = "tetris-replay 3";
+
+    pub fn new(seed: u64, rules: Rules, mode: GameMode) -> Replay
+    {
+        Replay { seed, rules, mode, frames: Vec::new() }
+    }
+    pub fn record(&mut self, delta_time: f32, inputs: &Inputs)
+    {
+        self.frames.push(ReplayFrame { delta_time, inputs: *inputs });
+    }
+    pub fn duration(&self) -> f32
+    {
+        self.frames.iter().map(|frame| frame.delta_time).sum()
+    }
+    //A fresh game with the recorded seed, rules and mode
+    pub fn create_state(&self) -> GameState
+    {
+        let mut state = GameState::with_seed(self.seed);
+        state.set_rules(self.rules);
+        state.set_mode(self.mode);
+        state
+    }
+    //Runs the whole replay headless and returns the final state
+    pub fn play(&self) -> GameState
+    {
+        let mut state = self.create_state();
+        for frame in self.frames.iter()
+        {
+            state.step(frame.delta_time, &frame.inputs);
+        }
+        state
+    }
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError>
+    {
+        if let Some(parent) = path.as_ref().parent()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, ReplayError>
+    {
+        Replay::parse(&fs::read_to_string(path)?)
+    }
+    pub fn parse(text: &str) -> Result<Replay, ReplayError>
+    {
+        let mut lines = text.lines().enumerate();
+        let header = lines.next().map(|(_i, line)| line).unwrap_or("");
+        if header != Replay::HEADER
+        {
+            return Err(ReplayError::UnsupportedVersion(header.to_string()));
+        }
+
+        let mut seed = None;
+        let mut rules = Rules::default();
+        let mut mode = GameMode::default();
+        let mut frames = Vec::new();
+        for (i, line) in lines
+        {
+            let malformed = || ReplayError::Malformed(i + 1, line.to_string());
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice()
+            {
+                [] => continue,
+                ["seed", value] => seed = Some(value.parse().map_err(|_| malformed())?),
+                ["mode", name] => mode = GameMode::from_name(name).ok_or_else(malformed)?,
+                ["rules", lock_delay, max_lock_resets, das, arr, width, height, buffer] =>
+                {
+                    rules = Rules
+                    {
+                        lock_delay: lock_delay.parse().map_err(|_| malformed())?,
+                        max_lock_resets: max_lock_resets.parse().map_err(|_| malformed())?,
+                        das: das.parse().map_err(|_| malformed())?,
+                        arr: arr.parse().map_err(|_| malformed())?,
+                        width: width.parse().map_err(|_| malformed())?,
+                        height: height.parse().map_err(|_| malformed())?,
+                        buffer: buffer.parse().map_err(|_| malformed())?
+                    };
+                    //A board the game couldn't have been played on would only crash on playback
+                    rules.check().map_err(|_| malformed())?;
+                }
+                [delta_time, bits] =>
+                {
+                    frames.push(ReplayFrame
+                    {
+                        delta_time: delta_time.parse().map_err(|_| malformed())?,
+                        inputs: decode_inputs(bits.parse().map_err(|_| malformed())?)
+                    });
+                }
+                _ => return Err(malformed())
+            }
+        }
+        let seed = seed.ok_or_else(|| ReplayError::Malformed(0, "missing seed".to_string()))?;
+        Ok(Replay { seed, rules, mode, frames })
+    }
+}
+impl fmt::Display for Replay
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "{}", Replay::HEADER)?;
+        writeln!(f, "seed {}", self.seed)?;
+        let rules = &self.rules;
+        writeln!(f, "rules {} {} {} {} {} {} {}", rules.lock_delay, rules.max_lock_resets, rules.das, rules.arr, rules.width, rules.height, rules.buffer)?;
+        writeln!(f, "mode {}", self.mode.name())?;
+        for frame in self.frames.iter()
+        {
+            writeln!(f, "{} {}", frame.delta_time, encode_inputs(&frame.inputs))?;
+        }
+        Ok(())
+    }
+}
+
+//Plays a replay back one recorded step at a time, or in real time with `advance`
+pub struct ReplayPlayer
+{
+    replay: Replay,
+    frame: usize,
+    //Seconds of playback asked for and seconds of recorded steps played
+    clock: f64,
+    played: f64
+}
+impl ReplayPlayer
+{
+    pub fn new(replay: Replay) -> ReplayPlayer
+    {
+        ReplayPlayer { replay, frame: 0, clock: 0.0, played: 0.0 }
+    }
+    pub fn replay(&self) -> &Replay
+    {
+        &self.replay
+    }
+    pub fn is_finished(&self) -> bool
+    {
+        self.frame >= self.replay.frames.len()
+    }
+    pub fn step(&mut self, state: &mut GameState) -> bool
+    {
+        match self.replay.frames.get(self.frame)
+        {
+            Some(frame) =>
+            {
+                state.step(frame.delta_time, &frame.inputs);
+                self.frame += 1;
+                self.played += frame.delta_time as f64;
+                true
+            }
+            None => false
+        }
+    }
+    //Plays every recorded step whose time has come after `delta_time` more seconds, so playback keeps the recorded speed whatever the display rate
+    pub fn advance(&mut self, delta_time: f32, state: &mut GameState)
+    {
+        self.clock += delta_time as f64;
+        while let Some(frame) = self.replay.frames.get(self.frame)
+        {
+            if self.played + frame.delta_time as f64 > self.clock + 1e-9
+            {
+                break;
+            }
+            self.step(state);
+        }
+    }
+}
+
+const LEFT: u32 = 1;
+const RIGHT: u32 = 1 << 1;
+const SOFT_DROP: u32 = 1 << 2;
+const ROTATE_CW: u32 = 1 << 3;
+const ROTATE_CCW: u32 = 1 << 4;
+const ROTATE_180: u32 = 1 << 5;
+const HARD_DROP: u32 = 1 << 6;
+const HOLD: u32 = 1 << 7;
+
+fn encode_inputs(inputs: &Inputs) -> u32
+{
+    let flags =
+    [
+        (inputs.horizontal < 0, LEFT),
+        (inputs.horizontal > 0, RIGHT),
+        (inputs.soft_drop, SOFT_DROP),
+        (inputs.rotate_cw, ROTATE_CW),
+        (inputs.rotate_ccw, ROTATE_CCW),
+        (inputs.rotate_180, ROTATE_180),
+        (inputs.hard_drop, HARD_DROP),
+        (inputs.hold, HOLD)
+    ];
+    flags.iter().filter(|(set, _bit)| *set).fold(0, |bits, (_set, bit)| bits | bit)
+}
+fn decode_inputs(bits: u32) -> Inputs
+{
+    Inputs
+    {
+        horizontal: if bits & LEFT != 0 { -1 } else if bits & RIGHT != 0 { 1 } else { 0 },
+        soft_drop: bits & SOFT_DROP != 0,
+        rotate_cw: bits & ROTATE_CW != 0,
+        rotate_ccw: bits & ROTATE_CCW != 0,
+        rotate_180: bits & ROTATE_180 != 0,
+        hard_drop: bits & HARD_DROP != 0,
+        hold: bits & HOLD != 0
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg32;
+
+    fn random_replay(seed: u64, frames: usize) -> Replay
+    {
+        let mut rng = Pcg32::seed_from_u64(seed);
+        let mut replay = Replay::new(seed, Rules::default(), GameMode::Sprint);
+        for _i in 0..frames
+        {
+            let inputs = Inputs
+            {
+                horizontal: rng.gen_range(-1, 2),
+                soft_drop: rng.gen_bool(0.3),
+                rotate_cw: rng.gen_bool(0.1),
+                rotate_ccw: rng.gen_bool(0.05),
+                rotate_180: rng.gen_bool(0.02),
+                hard_drop: rng.gen_bool(0.02),
+                hold: rng.gen_bool(0.01)
+            };
+            replay.record(rng.gen_range(0.005, 0.05), &inputs);
+        }
+        replay
+    }
+
+    #[test]
+    fn round_trips_through_text()
+    {
+        let replay = random_replay(5, 500);
+        assert_eq!(replay, Replay::parse(&replay.to_string()).unwrap());
+    }
+
+    #[test]
+    fn playback_reproduces_the_board()
+    {
+        let replay = random_replay(11, 3000);
+        let mut live = replay.create_state();
+        for frame in replay.frames.iter()
+        {
+            live.step(frame.delta_time, &frame.inputs);
+        }
+
+        let played = Replay::parse(&replay.to_string()).unwrap().play();
+        assert!(live.pieces_placed() > 0);
+        assert!(live.board() == played.board());
+        assert_eq!(live.score().score(), played.score().score());
+    }
+
+    #[test]
+    fn playback_follows_real_time()
+    {
+        //Recorded at 60 Hz, shown at 144 Hz
+        let mut replay = Replay::new(2, Rules::default(), GameMode::Endless);
+        for _i in 0..240
+        {
+            replay.record(GameState::FRAME_TIME as f32, &Inputs::default());
+        }
+        let mut player = ReplayPlayer::new(replay.clone());
+        let mut state = replay.create_state();
+        for _i in 0..144
+        {
+            player.advance(1.0 / 144.0, &mut state);
+        }
+        assert!((59..=60).contains(&state.frame()));
+        for _i in 0..500
+        {
+            player.advance(1.0 / 144.0, &mut state);
+        }
+        assert!(player.is_finished());
+        assert!(replay.play().board() == state.board());
+        assert_eq!(240, state.frame());
+    }
+
+    #[test]
+    fn rejects_unknown_versions()
+    {
+        assert!(matches!(Replay::parse("tetris-replay 99\nseed 1\n"), Err(ReplayError::UnsupportedVersion(_))));
+        assert!(matches!(Replay::parse("tetris-replay 3\nseed 1\n0.1 x\n"), Err(ReplayError::Malformed(3, _))));
+    }
+
+    #[test]
+    fn rejects_boards_that_cant_be_played()
+    {
+        let with_rules = |rules: Rules| Replay::parse(&Replay::new(1, rules, GameMode::Endless).to_string());
+        assert!(with_rules(Rules { width: 12, height: 30, ..Rules::default() }).is_ok());
+        for rules in [Rules { width: 0, ..Rules::default() }, Rules { width: 40, ..Rules::default() }, Rules { height: 0, ..Rules::default() }, Rules { buffer: 500, ..Rules::default() }].iter()
+        {
+            assert!(matches!(with_rules(*rules), Err(ReplayError::Malformed(3, _))));
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::ops::RangeInclusive;
+use crate::bitboard::BitBoard;
+
+//Board size and tunable timings for a game in logical frames, the defaults follow the guideline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules
+{
+    //Frames a grounded piece waits before locking
+    pub lock_delay: u32,
+    //Moves or rotations that may restart the lock delay before the piece locks on contact
+    pub max_lock_resets: u32,
+    //Delayed auto shift, frames a direction is held before it starts repeating
+    pub das: u32,
+    //Auto repeat rate, frames between repeats, 0 moves straight to the wall
+    pub arr: u32,
+    //Visible field in cells
+    pub width: u32,
+    pub height: u32,
+    //Hidden rows above the visible field, pieces spawn into them
+    pub buffer: u32
+}
+impl Default for Rules
+{
+    fn default() -> Rules
+    {
+        Rules
+        {
+            lock_delay: 30,
+            max_lock_resets: 15,
+            das: 10,
+            arr: 2,
+            width: 10,
+            height: 20,
+            buffer: 4
+        }
+    }
+}
+impl Rules
+{
+    //What the settings screen offers, rules from a file or a peer outside these are refused
+    pub const LOCK_DELAY: RangeInclusive<u32> = 5..=120;
+    //0 locks on the first grounded move, the guideline uses 15
+    pub const MAX_LOCK_RESETS: RangeInclusive<u32> = 0..=60;
+    pub const DAS: RangeInclusive<u32> = 1..=30;
+    pub const ARR: RangeInclusive<u32> = 0..=10;
+    pub const WIDTH: RangeInclusive<u32> = 4..=BitBoard::MAX_WIDTH;
+    pub const HEIGHT: RangeInclusive<u32> = 10..=40;
+    pub const BUFFER: RangeInclusive<u32> = 0..=20;
+
+    //Names the first value out of range
+    pub fn check(&self) -> Result<(), String>
+    {
+        let values = [
+            ("lock delay", self.lock_delay, Rules::LOCK_DELAY),
+            ("lock resets", self.max_lock_resets, Rules::MAX_LOCK_RESETS),
+            ("DAS", self.das, Rules::DAS),
+            ("ARR", self.arr, Rules::ARR),
+            ("width", self.width, Rules::WIDTH),
+            ("height", self.height, Rules::HEIGHT),
+            ("buffer", self.buffer, Rules::BUFFER)
+        ];
+        match values.iter().find(|(_, value, range)| !range.contains(value))
+        {
+            Some((name, value, range)) => Err(format!("{} {} is not between {} and {}", name, value, range.start(), range.end())),
+            None => Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn check_names_the_value_out_of_range()
+    {
+        assert_eq!(Ok(()), Rules::default().check());
+        assert_eq!(Ok(()), Rules { max_lock_resets: 0, ..Rules::default() }.check());
+        assert_eq!(Err("lock resets 61 is not between 0 and 60".to_string()), Rules { max_lock_resets: 61, ..Rules::default() }.check());
+        assert_eq!(Err("lock resets 4294967295 is not between 0 and 60".to_string()), Rules { max_lock_resets: u32::MAX, ..Rules::default() }.check());
+        assert_eq!(Err("width 3 is not between 4 and 32".to_string()), Rules { width: 3, ..Rules::default() }.check());
+    }
+}
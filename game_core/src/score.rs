@@ -0,0 +1,173 @@
+use crate::board::{ClearResult, TSpin};
+
+#[derive(Debug, Clone)]
+pub struct Score
+{
+    score: u32,
+    level: u32,
+    lines: u32,
+    combo: i32,
+    back_to_back: bool
+}
+impl Score
+{
+    pub const LINES_PER_LEVEL: u32 = 10;
+    const LINE_CLEAR_POINTS: [u32; 5] = [0, 100, 300, 500, 800];
+    const T_SPIN_MINI_POINTS: [u32; 3] = [100, 200, 400];
+    const T_SPIN_POINTS: [u32; 4] = [400, 800, 1200, 1600];
+    const PERFECT_CLEAR_POINTS: [u32; 5] = [0, 800, 1200, 1800, 2000];
+    const COMBO_POINTS: u32 = 50;
+    const SOFT_DROP_POINTS: u32 = 1;
+    const HARD_DROP_POINTS: u32 = 2;
+
+    pub fn new() -> Score
+    {
+        Score { score: 0, level: 1, lines: 0, combo: -1, back_to_back: false }
+    }
+    pub fn score(&self) -> u32
+    {
+        self.score
+    }
+    pub fn level(&self) -> u32
+    {
+        self.level
+    }
+    pub fn lines(&self) -> u32
+    {
+        self.lines
+    }
+    pub fn combo(&self) -> i32
+    {
+        self.combo
+    }
+    pub fn back_to_back(&self) -> bool
+    {
+        self.back_to_back
+    }
+    pub fn on_soft_drop(&mut self, cells: u32)
+    {
+        self.score += cells * Score::SOFT_DROP_POINTS;
+    }
+    pub fn on_hard_drop(&mut self, cells: u32)
+    {
+        self.score += cells * Score::HARD_DROP_POINTS;
+    }
+    //Returns the points awarded for the lock
+    pub fn on_lock(&mut self, result: &ClearResult) -> u32
+    {
+        let lines_cleared = result.lines().min(4);
+        let base = match result.t_spin
+        {
+            TSpin::None => Score::LINE_CLEAR_POINTS[lines_cleared as usize],
+            TSpin::Mini => Score::T_SPIN_MINI_POINTS[lines_cleared.min(2) as usize],
+            TSpin::Full => Score::T_SPIN_POINTS[lines_cleared.min(3) as usize]
+        };
+        let mut points = base * self.level;
+        if lines_cleared == 0
+        {
+            self.combo = -1;
+            self.score += points;
+            return points;
+        }
+        self.combo += 1;
+
+        //Tetrises and T-spins keep the back to back chain, other clears break it
+        let difficult = lines_cleared >= 4 || result.t_spin != TSpin::None;
+        if difficult && self.back_to_back
+        {
+            points += points / 2;
+        }
+        self.back_to_back = difficult;
+        points += Score::COMBO_POINTS * self.combo as u32 * self.level;
+        if result.perfect_clear
+        {
+            points += Score::PERFECT_CLEAR_POINTS[lines_cleared as usize] * self.level;
+        }
+
+        self.score += points;
+        self.lines += lines_cleared;
+        self.level = 1 + self.lines / Score::LINES_PER_LEVEL;
+        points
+    }
+    //Multiplier for the gravity delay, based on the guideline speed curve
+    pub fn gravity_factor(&self) -> f32
+    {
+        let level = self.level.min(20) as i32 - 1;
+        (0.8 - level as f32 * 0.007).powi(level)
+    }
+}
+impl Default for Score
+{
+    fn default() -> Score
+    {
+        Score::new()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn clear(lines: u32) -> ClearResult
+    {
+        ClearResult { rows: (0..lines).collect(), t_spin: TSpin::None, perfect_clear: false }
+    }
+    fn t_spin(t_spin: TSpin, lines: u32) -> ClearResult
+    {
+        ClearResult { t_spin, ..clear(lines) }
+    }
+
+    #[test]
+    fn line_clears_award_points_by_level()
+    {
+        let mut score = Score::new();
+        assert_eq!(100, score.on_lock(&clear(1)));
+        assert_eq!(0, score.on_lock(&clear(0)));
+        assert_eq!(300, score.on_lock(&clear(2)));
+        assert_eq!(0, score.on_lock(&clear(0)));
+        assert_eq!(500, score.on_lock(&clear(3)));
+        assert_eq!(0, score.on_lock(&clear(0)));
+        assert_eq!(800, score.on_lock(&clear(4)));
+        assert_eq!(10, score.lines());
+        assert_eq!(2, score.level());
+    }
+
+    #[test]
+    fn combos_and_back_to_back()
+    {
+        let mut score = Score::new();
+        assert_eq!(800, score.on_lock(&clear(4)));
+        assert_eq!(1200 + 50, score.on_lock(&clear(4)));
+        assert_eq!(100 + 100, score.on_lock(&clear(1)));
+        assert!(!score.back_to_back());
+        assert_eq!(0, score.on_lock(&clear(0)));
+        assert_eq!(-1, score.combo());
+    }
+
+    #[test]
+    fn t_spins_and_perfect_clears()
+    {
+        let mut score = Score::new();
+        assert_eq!(400, score.on_lock(&t_spin(TSpin::Full, 0)));
+        assert_eq!(-1, score.combo());
+        assert_eq!(200, score.on_lock(&t_spin(TSpin::Mini, 1)));
+        assert!(score.back_to_back());
+        assert_eq!(1200 * 3 / 2 + 50, score.on_lock(&t_spin(TSpin::Full, 2)));
+        assert_eq!(0, score.on_lock(&t_spin(TSpin::None, 0)));
+        assert!(score.back_to_back());
+        assert_eq!(100 + 800, score.on_lock(&ClearResult { perfect_clear: true, ..clear(1) }));
+        assert!(!score.back_to_back());
+    }
+
+    #[test]
+    fn gravity_speeds_up_with_level()
+    {
+        let mut score = Score::new();
+        assert_eq!(1.0, score.gravity_factor());
+        score.on_lock(&clear(4));
+        score.on_lock(&clear(4));
+        score.on_lock(&clear(4));
+        assert!(score.gravity_factor() < 1.0);
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt::Write;
+use std::time::Duration;
+use crate::bot::{Bot, BotPlayer};
+use crate::state::{GameState, GameOver};
+
+//Outcome of one bot game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameStats
+{
+    pub seed: u64,
+    pub pieces: u32,
+    pub lines: u32,
+    pub score: u32,
+    pub frames: u64,
+    pub topped_out: bool
+}
+impl GameStats
+{
+    pub const CSV_HEADER: &'static str = "seed,pieces,lines,score,frames,topped_out";
+
+    pub fn to_csv(&self) -> String
+    {
+        format!("{},{},{},{},{},{}", self.seed, self.pieces, self.lines, self.score, self.frames, self.topped_out)
+    }
+    pub fn to_json(&self) -> String
+    {
+        format!("{{\"seed\":{},\"pieces\":{},\"lines\":{},\"score\":{},\"frames\":{},\"topped_out\":{}}}", self.seed, self.pieces, self.lines, self.score, self.frames, self.topped_out)
+    }
+}
+
+//Plays a standard game with the bot until it tops out or has placed `max_pieces`
+pub fn play_game(bot: &Bot, seed: u64, max_pieces: u32) -> GameStats
+{
+    let mut state = GameState::with_seed(seed);
+    BotPlayer::new(bot.clone()).play(&mut state, max_pieces);
+    GameStats
+    {
+        seed,
+        pieces: state.pieces_placed(),
+        lines: state.score().lines(),
+        score: state.score().score(),
+        frames: state.frame(),
+        topped_out: state.game_over().is_some_and(GameOver::is_top_out)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary
+{
+    pub games: usize,
+    pub pieces: u64,
+    pub pieces_per_second: f64,
+    pub mean_lines: f64,
+    pub mean_score: f64,
+    pub min_score: u32,
+    pub median_score: u32,
+    pub max_score: u32,
+    pub top_out_rate: f64
+}
+impl Summary
+{
+    //`elapsed` is the wall clock time it took to play the games
+    pub fn new(games: &[GameStats], elapsed: Duration) -> Summary
+    {
+        let mut scores: Vec<u32> = games.iter().map(|game| game.score).collect();
+        scores.sort_unstable();
+        let count = games.len().max(1) as f64;
+        let pieces = games.iter().map(|game| game.pieces as u64).sum();
+        Summary
+        {
+            games: games.len(),
+            pieces,
+            pieces_per_second: pieces as f64 / elapsed.as_secs_f64().max(1e-9),
+            mean_lines: games.iter().map(|game| game.lines as f64).sum::<f64>() / count,
+            mean_score: scores.iter().map(|score| *score as f64).sum::<f64>() / count,
+            min_score: scores.first().copied().unwrap_or(0),
+            median_score: scores.get(scores.len() / 2).copied().unwrap_or(0),
+            max_score: scores.last().copied().unwrap_or(0),
+            top_out_rate: games.iter().filter(|game| game.topped_out).count() as f64 / count
+        }
+    }
+    pub fn to_json(&self, games: &[GameStats]) -> String
+    {
+        let mut json = String::new();
+        let _ = write!
+        (
+            json,
+            "{{\"games\":{},\"pieces\":{},\"pieces_per_second\":{:.1},\"mean_lines\":{:.2},\"mean_score\":{:.2},\"min_score\":{},\"median_score\":{},\"max_score\":{},\"top_out_rate\":{:.4},\"results\":[",
+            self.games, self.pieces, self.pieces_per_second, self.mean_lines, self.mean_score, self.min_score, self.median_score, self.max_score, self.top_out_rate
+        );
+        let results: Vec<String> = games.iter().map(GameStats::to_json).collect();
+        json.push_str(&results.join(","));
+        json.push_str("]}");
+        json
+    }
+    //One line per game, the summary goes in trailing comment lines so the table stays loadable
+    pub fn to_csv(&self, games: &[GameStats]) -> String
+    {
+        let mut csv = String::from(GameStats::CSV_HEADER);
+        for game in games
+        {
+            csv.push('\n');
+            csv.push_str(&game.to_csv());
+        }
+        let _ = write!
+        (
+            csv,
+            "\n# games={} pieces={} pieces_per_second={:.1} mean_lines={:.2} mean_score={:.2} min_score={} median_score={} max_score={} top_out_rate={:.4}",
+            self.games, self.pieces, self.pieces_per_second, self.mean_lines, self.mean_score, self.min_score, self.median_score, self.max_score, self.top_out_rate
+        );
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn stats(seed: u64, score: u32, topped_out: bool) -> GameStats
+    {
+        GameStats { seed, pieces: 10, lines: seed as u32, score, frames: 100, topped_out }
+    }
+
+    #[test]
+    fn summarises_games()
+    {
+        let games = [stats(0, 300, false), stats(1, 100, true), stats(2, 200, false), stats(3, 900, true)];
+        let summary = Summary::new(&games, Duration::from_secs(2));
+        assert_eq!(40, summary.pieces);
+        assert_eq!(20.0, summary.pieces_per_second);
+        assert_eq!(1.5, summary.mean_lines);
+        assert_eq!((100, 300, 900), (summary.min_score, summary.median_score, summary.max_score));
+        assert_eq!(0.5, summary.top_out_rate);
+        assert!(summary.to_json(&games).contains("{\"seed\":3,\"pieces\":10,\"lines\":3,\"score\":900,\"frames\":100,\"topped_out\":true}"));
+        assert_eq!(6, summary.to_csv(&games).lines().count());
+    }
+
+    #[test]
+    fn same_seed_same_game()
+    {
+        let bot = Bot::default();
+        assert_eq!(play_game(&bot, 5, 30), play_game(&bot, 5, 30));
+    }
+}
@@ -0,0 +1,908 @@
+use rand::{thread_rng, Rng};
+use crate::board::{Board, ClearResult, TSpin};
+use crate::tetromino::{Tetromino, TetrominoKind, RotationDirection};
+use crate::score::Score;
+use crate::randomizer::{Randomizer, RandomizerKind, PieceQueue};
+use crate::rules::Rules;
+use crate::mode::GameMode;
+use crate::garbage::{self, GarbageRules, GarbageQueue};
+use crate::utility::Point;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Inputs
+{
+    pub horizontal: i32,
+    pub soft_drop: bool,
+    pub rotate_cw: bool,
+    pub rotate_ccw: bool,
+    pub rotate_180: bool,
+    pub hard_drop: bool,
+    pub hold: bool
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOver
+{
+    //A new piece spawned overlapping the stack
+    BlockOut,
+    //A piece locked entirely inside the hidden buffer
+    LockOut,
+    //Incoming garbage pushed the stack out the top
+    TopOut,
+    //The mode's goal was reached or its time ran out
+    Completed
+}
+impl GameOver
+{
+    pub fn is_top_out(self) -> bool
+    {
+        self != GameOver::Completed
+    }
+}
+
+pub struct GameState
+{
+    board: Board,
+    active_piece: Tetromino,
+    shift_direction: i32,
+    shift_frames: u32,
+    shift_repeats: u32,
+    gravity_progress: f32,
+    lock_frames: u32,
+    accumulator: f64,
+    pending: Inputs,
+    frame: u64,
+    lock_resets: u32,
+    lowest_row: i32,
+    //Direction and kick index of the rotation that moved the piece last
+    last_rotation: Option<(RotationDirection, usize)>,
+    last_clear: Option<ClearResult>,
+    rules: Rules,
+    mode: GameMode,
+    queue: PieceQueue,
+    seed: u64,
+    hold_piece: Option<Tetromino>,
+    can_hold: bool,
+    score: Score,
+    pieces_placed: u32,
+    garbage: GarbageQueue,
+    //Sent lines the opponent hasn't picked up yet
+    outgoing: u32,
+    lines_sent: u32,
+    game_over: Option<GameOver>,
+}
+impl GameState
+{
+    pub const FRAME_RATE: u32 = 60;
+    pub const FRAME_TIME: f64 = 1.0 / GameState::FRAME_RATE as f64;
+    const GRAVITY_FRAMES: f32 = 30.0;
+    const SOFT_DROP_FRAMES: f32 = 12.0;
+    const PREVIEW_SIZE: usize = 6;
+
+    pub fn new() -> GameState
+    {
+        GameState::with_seed(GameState::random_seed())
+    }
+    //What unseeded games start from
+    pub fn random_seed() -> u64
+    {
+        thread_rng().gen()
+    }
+    //Standard 7-bag game, the same seed always deals the same pieces
+    pub fn with_seed(seed: u64) -> GameState
+    {
+        GameState::with_randomizer(RandomizerKind::Bag7.create(seed), seed)
+    }
+    pub fn with_randomizer(randomizer: Box<dyn Randomizer>, seed: u64) -> GameState
+    {
+        let mut queue = PieceQueue::new(randomizer, GameState::PREVIEW_SIZE);
+        queue.reset(seed);
+        let rules = Rules::default();
+        let board = Board::with_buffer(rules.width, rules.height, rules.buffer);
+        let active_piece = board.spawn(queue.pop());
+        GameState 
+        { 
+            board, 
+            active_piece, 
+            shift_direction: 0,
+            shift_frames: 0,
+            shift_repeats: 0,
+            gravity_progress: 0.0,
+            lock_frames: 0,
+            accumulator: 0.0,
+            pending: Inputs::default(),
+            frame: 0,
+            lock_resets: 0,
+            lowest_row: 0,
+            last_rotation: None,
+            last_clear: None,
+            rules,
+            mode: GameMode::default(),
+            queue,
+            seed,
+            hold_piece: None,
+            can_hold: true,
+            score: Score::new(),
+            pieces_placed: 0,
+            garbage: GarbageQueue::new(GarbageRules::default(), seed),
+            outgoing: 0,
+            lines_sent: 0,
+            game_over: None
+        }
+    }
+    pub fn restart(&mut self)
+    {
+        self.restart_with_seed(thread_rng().gen());
+    }
+    //Resets everything but keeps the randomizer type
+    pub fn restart_with_seed(&mut self, seed: u64)
+    {
+        self.queue.reset(seed);
+        self.board = Board::with_buffer(self.rules.width, self.rules.height, self.rules.buffer);
+        self.active_piece = self.board.spawn(self.queue.pop());
+        self.shift_direction = 0;
+        self.gravity_progress = 0.0;
+        self.accumulator = 0.0;
+        self.pending = Inputs::default();
+        self.frame = 0;
+        self.reset_lock();
+        self.seed = seed;
+        self.hold_piece = None;
+        self.can_hold = true;
+        self.score = Score::new();
+        self.pieces_placed = 0;
+        self.garbage.reset(seed);
+        self.outgoing = 0;
+        self.lines_sent = 0;
+        self.last_clear = None;
+        self.game_over = None;
+    }
+    pub fn rules(&self) -> &Rules
+    {
+        &self.rules
+    }
+    //A different board size starts the game over on the same seed
+    pub fn set_rules(&mut self, rules: Rules)
+    {
+        let resized = (rules.width, rules.height, rules.buffer) != (self.rules.width, self.rules.height, self.rules.buffer);
+        self.rules = rules;
+        if resized
+        {
+            self.restart_with_seed(self.seed);
+        }
+    }
+    pub fn mode(&self) -> GameMode
+    {
+        self.mode
+    }
+    //Kept across restarts, set it before the game starts
+    pub fn set_mode(&mut self, mode: GameMode)
+    {
+        self.mode = mode;
+    }
+    pub fn garbage_rules(&self) -> &GarbageRules
+    {
+        self.garbage.rules()
+    }
+    pub fn set_garbage_rules(&mut self, rules: GarbageRules)
+    {
+        self.garbage.set_rules(rules);
+    }
+    //Queues lines from an opponent, they rise after the delay when a piece locks without clearing
+    pub fn receive_garbage(&mut self, lines: u32)
+    {
+        self.garbage.push(lines);
+    }
+    //Lines sent since the last call, for whoever passes them on to the opponent
+    pub fn take_outgoing(&mut self) -> u32
+    {
+        std::mem::take(&mut self.outgoing)
+    }
+    pub fn incoming_garbage(&self) -> u32
+    {
+        self.garbage.pending()
+    }
+    //Lines sent over the whole game, after cancelling
+    pub fn lines_sent(&self) -> u32
+    {
+        self.lines_sent
+    }
+    pub fn seed(&self) -> u64
+    {
+        self.seed
+    }
+    pub fn board(&self) -> &Board
+    {
+        &self.board
+    }
+    pub fn active_piece(&self) -> &Tetromino
+    {
+        &self.active_piece
+    }
+    pub fn score(&self) -> &Score
+    {
+        &self.score
+    }
+    //The result of the last lock that cleared lines or spun
+    pub fn last_clear(&self) -> Option<&ClearResult>
+    {
+        self.last_clear.as_ref()
+    }
+    pub fn pieces_placed(&self) -> u32
+    {
+        self.pieces_placed
+    }
+    pub fn game_over(&self) -> Option<GameOver>
+    {
+        self.game_over
+    }
+    pub fn is_game_over(&self) -> bool
+    {
+        self.game_over.is_some()
+    }
+    pub fn hold_piece(&self) -> Option<&Tetromino>
+    {
+        self.hold_piece.as_ref()
+    }
+    pub fn can_hold(&self) -> bool
+    {
+        self.can_hold
+    }
+    pub fn next_pieces(&self) -> impl Iterator<Item = &TetrominoKind>
+    {
+        self.queue.preview()
+    }
+    pub fn get_drop_position(&self, tetromino: &Tetromino) -> Point
+    {
+        tetromino.position + Point{ x: 0, y: self.board.drop_distance(tetromino) }
+    }
+    fn apply_piece_to_board(&mut self)
+    {        
+        //Without top outs the piece locks and the next one blocks out and empties the board
+        if self.mode.tops_out() && self.board.is_lock_out(&self.active_piece)
+        {
+            self.game_over = Some(GameOver::LockOut);
+            return;
+        }
+        let result = self.board.lock_piece(&self.active_piece, self.last_rotation);
+        let back_to_back = self.score.back_to_back();
+        self.score.on_lock(&result);
+        //Attacks cancel incoming garbage first, what is left is sent
+        let attack = self.garbage.cancel(garbage::attack_lines(&result, self.score.combo(), back_to_back));
+        self.outgoing += attack;
+        self.lines_sent += attack;
+        let cleared = result.lines() > 0;
+        if cleared || result.t_spin != TSpin::None
+        {
+            self.last_clear = Some(result);
+        }
+        self.pieces_placed += 1;
+        self.can_hold = true;
+        //The goal line ends the game before another piece can block out
+        if self.mode.is_complete(self)
+        {
+            self.game_over = Some(GameOver::Completed);
+            return;
+        }
+        if !cleared && !self.raise_garbage()
+        {
+            return;
+        }
+        self.spawn_next_piece();
+    }
+    //False if the garbage topped the player out
+    fn raise_garbage(&mut self) -> bool
+    {
+        let holes = self.garbage.take_ready(self.board.width());
+        if holes.is_empty() || !self.board.insert_garbage(&holes)
+        {
+            return true;
+        }
+        if self.mode.tops_out()
+        {
+            self.game_over = Some(GameOver::TopOut);
+            return false;
+        }
+        self.board = Board::with_buffer(self.rules.width, self.rules.height, self.rules.buffer);
+        true
+    }
+    fn spawn_next_piece(&mut self)
+    {
+        self.active_piece = self.board.spawn(self.queue.pop());
+        self.check_block_out();
+    }
+    fn reset_lock(&mut self)
+    {
+        self.last_rotation = None;
+        self.lock_frames = 0;
+        self.lock_resets = 0;
+        self.lowest_row = self.active_piece.position.y;
+    }
+    fn is_grounded(&self) -> bool
+    {
+        let mut below = self.active_piece.clone();
+        below.position.y += 1;
+        self.board.check_collision(&below)
+    }
+    //Called after every successful move or rotation
+    fn on_piece_moved(&mut self)
+    {
+        if self.active_piece.position.y > self.lowest_row
+        {
+            self.lowest_row = self.active_piece.position.y;
+            self.lock_resets = 0;
+        }
+        if self.is_grounded() && self.lock_resets < self.rules.max_lock_resets
+        {
+            self.lock_frames = 0;
+            self.lock_resets += 1;
+        }
+    }
+    fn check_block_out(&mut self)
+    {
+        self.reset_lock();
+        if !self.board.check_collision(&self.active_piece)
+        {
+            return;
+        }
+        if self.mode.tops_out()
+        {
+            self.game_over = Some(GameOver::BlockOut);
+        }
+        else
+        {
+            self.board = Board::with_buffer(self.rules.width, self.rules.height, self.rules.buffer);
+        }
+    }
+    //Number of cells to shift this frame, one on press then repeating after DAS
+    fn auto_shift(&mut self, direction: i32) -> u32
+    {
+        if direction != self.shift_direction
+        {
+            self.shift_direction = direction;
+            self.shift_frames = 0;
+            self.shift_repeats = 0;
+            return if direction == 0 { 0 } else { 1 };
+        }
+        if direction == 0
+        {
+            return 0;
+        }
+
+        self.shift_frames += 1;
+        if self.shift_frames < self.rules.das
+        {
+            return 0;
+        }
+        if self.rules.arr == 0
+        {
+            return self.board.width;
+        }
+        let repeats = 1 + (self.shift_frames - self.rules.das) / self.rules.arr;
+        let shifts = repeats - self.shift_repeats;
+        self.shift_repeats = repeats;
+        shifts
+    }
+    //Rows fallen per frame
+    fn gravity(&self, soft_drop: bool) -> f32
+    {
+        let frames = GameState::GRAVITY_FRAMES * self.score.gravity_factor();
+        let frames = if soft_drop { frames.min(GameState::SOFT_DROP_FRAMES) } else { frames };
+        1.0 / frames
+    }
+    //Fraction of a row the active piece has fallen towards the next, for smooth rendering
+    pub fn fall_progress(&self) -> f32
+    {
+        if self.is_game_over() || self.is_grounded()
+        {
+            return 0.0;
+        }
+        let interpolation = (self.accumulator / GameState::FRAME_TIME) as f32;
+        (self.gravity_progress + interpolation * self.gravity(self.pending.soft_drop)).min(0.99)
+    }
+    //FNV-1a over everything that decides what happens next, two games with the same checksum are almost certainly in sync
+    pub fn checksum(&self) -> u64
+    {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut add = |value: u64|
+        {
+            for byte in value.to_le_bytes().iter()
+            {
+                hash = (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        add(self.frame);
+        for y in 0..self.board.height()
+        {
+            add(self.board.bits().row(y) as u64);
+        }
+        let piece = &self.active_piece;
+        add(piece.kind as u64);
+        add(piece.rotation as u64);
+        add(piece.position.x as u64);
+        add(piece.position.y as u64);
+        add(self.hold_piece.as_ref().map_or(0, |piece| piece.kind as u64 + 1));
+        add(self.gravity_progress.to_bits() as u64);
+        add(self.lock_frames as u64);
+        add(self.score.score() as u64);
+        add(self.lines_sent as u64);
+        add(self.garbage.pending() as u64);
+        add(self.game_over.map_or(0, |reason| reason as u64 + 1));
+        hash
+    }
+    //Logical frames simulated since the start
+    pub fn frame(&self) -> u64
+    {
+        self.frame
+    }
+    //Seconds of game time, counted in logical frames so pauses and lag don't add to it
+    pub fn elapsed(&self) -> f64
+    {
+        self.frame as f64 * GameState::FRAME_TIME
+    }
+    fn hold(&mut self)
+    {
+        if !self.can_hold
+        {
+            return;
+        }
+        self.can_hold = false;
+
+        let held = Tetromino::from_kind(self.active_piece.kind);
+        match self.hold_piece.replace(held)
+        {
+            Some(piece) => { self.active_piece = self.board.spawn(piece.kind); self.check_block_out(); }
+            None => { self.spawn_next_piece(); }
+        }
+        self.gravity_progress = 0.0;
+    }
+    //Advances by whole logical frames, leftover time is kept for the next step.
+    //Presses are kept until a frame has consumed them so none are lost between frames
+    pub fn step(&mut self, delta_time: f32, inputs: &Inputs)
+    {
+        self.accumulator += delta_time as f64;
+        self.pending = Inputs
+        {
+            horizontal: inputs.horizontal,
+            soft_drop: inputs.soft_drop,
+            rotate_cw: self.pending.rotate_cw || inputs.rotate_cw,
+            rotate_ccw: self.pending.rotate_ccw || inputs.rotate_ccw,
+            rotate_180: self.pending.rotate_180 || inputs.rotate_180,
+            hard_drop: self.pending.hard_drop || inputs.hard_drop,
+            hold: self.pending.hold || inputs.hold
+        };
+        //Small tolerance so n * FRAME_TIME always gives n frames
+        while self.accumulator + 1e-9 >= GameState::FRAME_TIME
+        {
+            self.accumulator -= GameState::FRAME_TIME;
+            let inputs = self.pending;
+            self.step_frame(&inputs);
+            self.pending = Inputs { horizontal: inputs.horizontal, soft_drop: inputs.soft_drop, ..Inputs::default() };
+        }
+    }
+    //Like step, but asks for the inputs of every logical frame, for bots and other frame exact sources
+    pub fn step_with<F: FnMut(&GameState) -> Inputs>(&mut self, delta_time: f32, mut inputs: F)
+    {
+        self.accumulator += delta_time as f64;
+        while self.accumulator + 1e-9 >= GameState::FRAME_TIME
+        {
+            self.accumulator -= GameState::FRAME_TIME;
+            let inputs = inputs(self);
+            self.step_frame(&inputs);
+        }
+    }
+    pub fn step_frame(&mut self, inputs: &Inputs)
+    {
+        if self.is_game_over()
+        {
+            return;
+        }
+        self.frame += 1;
+        self.garbage.tick();
+
+        if inputs.hold
+        {
+            self.hold();
+            if self.is_game_over()
+            {
+                return;
+            }
+        }
+
+        //Gravity
+        self.gravity_progress += self.gravity(inputs.soft_drop);
+        while self.gravity_progress >= 1.0
+        {
+            if self.is_grounded()
+            {
+                self.gravity_progress = 0.0;
+                break;
+            }
+            self.gravity_progress -= 1.0;
+            self.active_piece.position.y += 1;
+            self.last_rotation = None;
+            self.on_piece_moved();
+            if inputs.soft_drop
+            {
+                self.score.on_soft_drop(1);
+            }
+        }
+        
+        let shifts = self.auto_shift(inputs.horizontal);
+        for _i in 0..shifts
+        {
+            self.active_piece.position.x += inputs.horizontal;
+            //Collision side
+            if self.board.check_collision(&self.active_piece)
+            {
+                self.active_piece.position.x -= inputs.horizontal;
+                break;
+            }
+            self.last_rotation = None;
+            self.on_piece_moved();
+        }
+
+        let rotation = 
+            if inputs.rotate_cw { Some(RotationDirection::Clockwise) }
+            else if inputs.rotate_ccw { Some(RotationDirection::CounterClockwise) }
+            else if inputs.rotate_180 { Some(RotationDirection::Half) }
+            else { None };
+        if let Some(direction) = rotation
+        {
+            if let Some(kick) = self.active_piece.rotate(&self.board, direction)
+            {
+                self.last_rotation = Some((direction, kick));
+                self.on_piece_moved();
+            }
+        }
+        
+        if inputs.hard_drop
+        {
+            let drop_position = self.get_drop_position(&self.active_piece);
+            if drop_position.y != self.active_piece.position.y
+            {
+                self.last_rotation = None;
+            }
+            self.score.on_hard_drop((drop_position.y - self.active_piece.position.y) as u32);
+            self.active_piece.position = drop_position;
+            self.apply_piece_to_board();
+        }
+        //Lock
+        else if self.is_grounded()
+        {
+            self.lock_frames += 1;
+            if self.lock_frames >= self.rules.lock_delay || self.lock_resets >= self.rules.max_lock_resets
+            {
+                self.apply_piece_to_board();
+            }
+        }
+        else
+        {
+            self.lock_frames = 0;
+        }
+
+        if !self.is_game_over() && self.mode.is_complete(self)
+        {
+            self.game_over = Some(GameOver::Completed);
+        }
+    }
+}
+impl Default for GameState
+{
+    fn default() -> GameState
+    {
+        GameState::new()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::board::Cell;
+
+    #[test]
+    fn hard_drop_lands_on_floor()
+    {
+        let mut state = GameState::new();
+        let first = state.active_piece().clone();
+        state.step_frame(&Inputs { hard_drop: true, ..Inputs::default() });
+
+        let bottom = state.board().height() - 1;
+        let occupied = (0..state.board().width()).filter(|x| matches!(state.board().get_cell(*x, bottom), Cell::Occupied(_))).count();
+        assert!(occupied > 0);
+        assert_eq!(first.kind, state.board().cells.iter().find_map(|c| match c { Cell::Occupied(kind) => Some(*kind), _ => None }).unwrap());
+    }
+
+    #[test]
+    fn hold_swaps_once_per_lock()
+    {
+        let mut state = GameState::new();
+        let first = state.active_piece().kind;
+        let second = *state.next_pieces().next().unwrap();
+        let hold = Inputs { hold: true, ..Inputs::default() };
+
+        state.step_frame(&hold);
+        assert_eq!(Some(first), state.hold_piece().map(|piece| piece.kind));
+        assert_eq!(second, state.active_piece().kind);
+
+        state.step_frame(&hold);
+        assert_eq!(Some(first), state.hold_piece().map(|piece| piece.kind));
+        assert_eq!(second, state.active_piece().kind);
+
+        state.step_frame(&Inputs { hard_drop: true, ..Inputs::default() });
+        let third = state.active_piece().kind;
+        state.step_frame(&hold);
+        assert_eq!(Some(third), state.hold_piece().map(|piece| piece.kind));
+        assert_eq!(first, state.active_piece().kind);
+    }
+
+    #[test]
+    fn stacking_to_the_top_blocks_out()
+    {
+        //Seeded, with the buffer some piece orders lock out first instead
+        let mut state = GameState::with_seed(4);
+        let hard_drop = Inputs { hard_drop: true, ..Inputs::default() };
+        for _i in 0..100
+        {
+            state.step_frame(&hard_drop);
+        }
+        assert_eq!(Some(GameOver::BlockOut), state.game_over());
+
+        let pieces = state.pieces_placed();
+        state.step_frame(&hard_drop);
+        assert_eq!(pieces, state.pieces_placed());
+
+        state.restart();
+        assert!(!state.is_game_over());
+        assert_eq!(0, state.pieces_placed());
+        assert!(!state.board().check_collision(state.active_piece()));
+    }
+
+    #[test]
+    fn locking_above_the_field_locks_out()
+    {
+        let mut state = GameState::new();
+        state.active_piece.position.y = 0;
+        state.apply_piece_to_board();
+        assert_eq!(Some(GameOver::LockOut), state.game_over());
+    }
+
+    #[test]
+    fn garbage_rises_after_a_lock_and_can_top_out()
+    {
+        let mut state = GameState::with_seed(3);
+        state.set_garbage_rules(GarbageRules { messiness: 0, delay: 1, cap: 30 });
+        state.receive_garbage(4);
+        assert_eq!(4, state.incoming_garbage());
+        let hard_drop = Inputs { hard_drop: true, ..Inputs::default() };
+        state.step_frame(&hard_drop);
+        assert_eq!(0, state.incoming_garbage());
+        let board = state.board();
+        let bottom = board.height() - 1;
+        let garbage = (0..board.width()).filter(|x| *board.get_cell(*x, bottom) == Cell::Garbage).count();
+        assert_eq!(board.width() as usize - 1, garbage);
+        assert!(!state.is_game_over());
+
+        state.receive_garbage(board.height());
+        state.step_frame(&Inputs::default());
+        state.step_frame(&hard_drop);
+        assert_eq!(Some(GameOver::TopOut), state.game_over());
+    }
+
+    #[test]
+    fn pieces_spawn_centred_at_the_top_of_the_field()
+    {
+        let board = Board::with_buffer(10, 20, 4);
+        let columns = |piece: &Tetromino| piece.points.iter().map(|point| point.x + piece.position.x).collect::<Vec<i32>>();
+        let t = board.spawn(TetrominoKind::T);
+        assert_eq!((3, 5), (*columns(&t).iter().min().unwrap(), *columns(&t).iter().max().unwrap()));
+        let i = board.spawn(TetrominoKind::I);
+        assert_eq!((3, 6), (*columns(&i).iter().min().unwrap(), *columns(&i).iter().max().unwrap()));
+        let o = board.spawn(TetrominoKind::O);
+        assert_eq!((4, 5), (*columns(&o).iter().min().unwrap(), *columns(&o).iter().max().unwrap()));
+        //Bottom row in the first visible row, the rest still in the buffer
+        assert_eq!(4, t.points.iter().map(|point| point.y + t.position.y).max().unwrap());
+
+        let mut state = GameState::with_seed(5);
+        state.set_rules(Rules { width: 6, height: 12, buffer: 2, ..Rules::default() });
+        assert_eq!((6, 14, 12), (state.board().width(), state.board().height(), state.board().visible_height()));
+        assert!(!state.board().check_collision(state.active_piece()));
+    }
+
+    #[test]
+    fn seeded_games_deal_the_same_pieces()
+    {
+        let mut first = GameState::with_seed(1234);
+        let mut second = GameState::with_seed(1234);
+        let hard_drop = Inputs { hard_drop: true, ..Inputs::default() };
+        for _i in 0..20
+        {
+            assert_eq!(first.active_piece().kind, second.active_piece().kind);
+            assert!(first.next_pieces().eq(second.next_pieces()));
+            first.step_frame(&hard_drop);
+            second.step_frame(&hard_drop);
+        }
+
+        first.restart_with_seed(1234);
+        let mut fresh = GameState::with_seed(1234);
+        assert_eq!(fresh.active_piece().kind, first.active_piece().kind);
+        assert!(fresh.next_pieces().eq(first.next_pieces()));
+        fresh.restart_with_seed(99);
+        assert_eq!(99, fresh.seed());
+    }
+
+    #[test]
+    fn sprint_ends_on_the_frame_of_the_last_line()
+    {
+        let mut state = GameState::with_seed(8);
+        state.set_mode(GameMode::Sprint);
+        let goal = GameMode::Sprint.line_goal().unwrap_or(0);
+        for _i in 0..goal
+        {
+            assert!(!state.is_game_over());
+            //Every drop fills the bottom row of an empty board
+            state.board = Board::with_buffer(state.rules.width, state.rules.height, state.rules.buffer);
+            let mut dropped = state.active_piece.clone();
+            dropped.position = state.get_drop_position(&dropped);
+            let bottom = state.board.height() - 1;
+            for x in 0..state.board.width()
+            {
+                if !dropped.points.iter().any(|point| (point.x + dropped.position.x, point.y + dropped.position.y) == (x as i32, bottom as i32))
+                {
+                    state.board.set_cell(x, bottom, Cell::Garbage);
+                }
+            }
+            state.step_frame(&Inputs { hard_drop: true, ..Inputs::default() });
+        }
+        assert_eq!(goal, state.score().lines());
+        assert_eq!(Some(GameOver::Completed), state.game_over());
+        assert_eq!(goal as u64, state.frame());
+    }
+
+    fn ground(state: &mut GameState)
+    {
+        state.active_piece.position = state.get_drop_position(&state.active_piece);
+        state.step_frame(&Inputs::default());
+        assert!(state.is_grounded());
+    }
+    fn step_frames(state: &mut GameState, frames: u32, inputs: &Inputs)
+    {
+        for _i in 0..frames
+        {
+            state.step_frame(inputs);
+        }
+    }
+
+    #[test]
+    fn grounded_piece_waits_for_lock_delay()
+    {
+        let mut state = GameState::with_seed(7);
+        ground(&mut state);
+        let delay = state.rules().lock_delay;
+
+        step_frames(&mut state, delay - 2, &Inputs::default());
+        assert_eq!(0, state.pieces_placed());
+        state.step_frame(&Inputs::default());
+        assert_eq!(1, state.pieces_placed());
+    }
+
+    #[test]
+    fn moving_resets_lock_delay()
+    {
+        let mut state = GameState::with_seed(7);
+        ground(&mut state);
+        let delay = state.rules().lock_delay;
+
+        step_frames(&mut state, delay - 5, &Inputs::default());
+        state.step_frame(&Inputs { horizontal: 1, ..Inputs::default() });
+        step_frames(&mut state, delay - 5, &Inputs::default());
+        assert_eq!(0, state.pieces_placed());
+        step_frames(&mut state, 5, &Inputs::default());
+        assert_eq!(1, state.pieces_placed());
+    }
+
+    #[test]
+    fn lock_resets_are_capped()
+    {
+        let mut state = GameState::with_seed(7);
+        state.set_rules(Rules { lock_delay: 600, max_lock_resets: 3, ..Rules::default() });
+        ground(&mut state);
+
+        for direction in [1, -1].iter()
+        {
+            state.step_frame(&Inputs { horizontal: *direction, ..Inputs::default() });
+            assert_eq!(0, state.pieces_placed());
+        }
+        state.step_frame(&Inputs { horizontal: 1, ..Inputs::default() });
+        assert_eq!(1, state.pieces_placed());
+    }
+
+    fn held_right(state: &mut GameState, frames: u32) -> i32
+    {
+        let start = state.active_piece().position.x;
+        step_frames(state, frames, &Inputs { horizontal: 1, ..Inputs::default() });
+        state.active_piece().position.x - start
+    }
+
+    #[test]
+    fn das_then_arr_repeats()
+    {
+        let mut state = GameState::with_seed(3);
+        state.set_rules(Rules { das: 10, arr: 3, lock_delay: 600, ..Rules::default() });
+        state.active_piece.position.x = 0;
+
+        assert_eq!(1, held_right(&mut state, 1));
+        assert_eq!(0, held_right(&mut state, 5));
+        assert_eq!(0, held_right(&mut state, 3));
+        assert_eq!(1, held_right(&mut state, 2));
+        assert_eq!(2, held_right(&mut state, 6));
+
+        state.step_frame(&Inputs::default());
+        assert_eq!(1, held_right(&mut state, 1));
+    }
+
+    #[test]
+    fn zero_arr_shifts_to_wall()
+    {
+        let mut state = GameState::with_seed(3);
+        state.set_rules(Rules { das: 5, arr: 0, lock_delay: 600, ..Rules::default() });
+
+        held_right(&mut state, 6);
+        let (_top_left, bottom_right) = state.active_piece().generate_bounds();
+        assert_eq!(state.board().width() as i32 - 1, bottom_right.x);
+    }
+
+    #[test]
+    fn switching_direction_shifts_immediately()
+    {
+        let mut state = GameState::with_seed(3);
+        state.active_piece.position.x = 4;
+        held_right(&mut state, 1);
+
+        let start = state.active_piece().position.x;
+        state.step_frame(&Inputs { horizontal: -1, ..Inputs::default() });
+        assert_eq!(start - 1, state.active_piece().position.x);
+    }
+
+    #[test]
+    fn gravity_moves_piece_down()
+    {
+        let mut state = GameState::new();
+        let start = state.active_piece().position;
+        step_frames(&mut state, GameState::GRAVITY_FRAMES as u32, &Inputs::default());
+        assert_eq!(start.y + 1, state.active_piece().position.y);
+    }
+
+    #[test]
+    fn presses_between_frames_are_kept()
+    {
+        let mut state = GameState::with_seed(5);
+        state.step(0.0, &Inputs { hard_drop: true, ..Inputs::default() });
+        assert_eq!(0, state.pieces_placed());
+        state.step(GameState::FRAME_TIME as f32, &Inputs::default());
+        assert_eq!(1, state.pieces_placed());
+        state.step(GameState::FRAME_TIME as f32, &Inputs::default());
+        assert_eq!(1, state.pieces_placed());
+    }
+
+    #[test]
+    fn frame_rate_does_not_change_the_game()
+    {
+        let inputs = [Inputs { horizontal: 1, ..Inputs::default() }, Inputs::default(), Inputs { soft_drop: true, ..Inputs::default() }];
+        let mut results = Vec::new();
+        for fps in [24.0, 60.0, 144.0].iter()
+        {
+            let mut state = GameState::with_seed(21);
+            for input in inputs.iter()
+            {
+                for _i in 0..(*fps as u32) * 3
+                {
+                    state.step(1.0 / fps, input);
+                }
+            }
+            results.push((state.frame(), state.board().clone(), state.active_piece().position, state.score().score()));
+        }
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+}
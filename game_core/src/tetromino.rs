@@ -0,0 +1,402 @@
+use crate::utility::Point;
+use std::cmp;
+use crate::board::Board;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TetrominoKind
+{
+    I,
+    J,
+    L,
+    O,
+    S,
+    T,
+    Z
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rotation
+{
+    Zero,
+    Right,
+    Two,
+    Left
+}
+impl Rotation
+{
+    pub fn rotated(self, direction: RotationDirection) -> Rotation
+    {
+        let steps = match direction
+        {
+            RotationDirection::Clockwise => 1,
+            RotationDirection::Half => 2,
+            RotationDirection::CounterClockwise => 3
+        };
+        Rotation::from_index((self.index() + steps) % 4)
+    }
+    fn index(self) -> usize
+    {
+        match self
+        {
+            Rotation::Zero => 0,
+            Rotation::Right => 1,
+            Rotation::Two => 2,
+            Rotation::Left => 3
+        }
+    }
+    fn from_index(index: usize) -> Rotation
+    {
+        match index
+        {
+            0 => Rotation::Zero,
+            1 => Rotation::Right,
+            2 => Rotation::Two,
+            _ => Rotation::Left
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationDirection
+{
+    Clockwise,
+    CounterClockwise,
+    Half
+}
+
+#[derive(Debug, Clone)]
+pub struct Tetromino
+{
+    pub kind: TetrominoKind,
+    pub position: Point,
+    pub points: [Point; 4],
+    pub rotation: Rotation,
+    box_size: i32
+    
+}
+impl Tetromino
+{
+    pub fn i() -> Tetromino
+    {
+        Tetromino
+        {
+            kind: TetrominoKind::I,
+            position: Point::zero(),
+            points: [Point{x: 0, y: 1}, Point{x: 1, y: 1}, Point{x: 2, y: 1}, Point{x: 3, y: 1}],
+            rotation: Rotation::Zero,
+            box_size: 4
+        }
+    }
+    pub fn j() -> Tetromino
+    {
+        Tetromino
+        {
+            kind: TetrominoKind::J,
+            position: Point::zero(),
+            points: [Point{x: 0, y: 0}, Point{x: 0, y: 1}, Point{x: 1, y: 1}, Point{x: 2, y: 1}],
+            rotation: Rotation::Zero,
+            box_size: 3
+        }
+    }
+    pub fn l() -> Tetromino
+    {
+        Tetromino
+        {
+            kind: TetrominoKind::L,
+            position: Point::zero(),
+            points: [Point{x: 0, y: 1}, Point{x: 1, y: 1}, Point{x: 2, y: 1}, Point{x: 2, y: 0}],
+            rotation: Rotation::Zero,
+            box_size: 3
+        }
+    }
+    pub fn o() -> Tetromino
+    {
+        Tetromino
+        {
+            kind: TetrominoKind::O,
+            position: Point::zero(),
+            points: [Point{x: 0, y: 0}, Point{x: 0, y: 1}, Point{x: 1, y: 1}, Point{x: 1, y: 0}],
+            rotation: Rotation::Zero,
+            box_size: 2
+        }
+    }
+    pub fn s() -> Tetromino
+    {
+        Tetromino
+        {
+            kind: TetrominoKind::S,
+            position: Point::zero(),
+            points: [Point{x: 0, y: 1}, Point{x: 1, y: 1}, Point{x: 1, y: 0}, Point{x: 2, y: 0}],
+            rotation: Rotation::Zero,
+            box_size: 3
+        }
+    }
+    pub fn t() -> Tetromino
+    {
+        Tetromino
+        {
+            kind: TetrominoKind::T,
+            position: Point::zero(),
+            points: [Point{x: 0, y: 1}, Point{x: 1, y: 1}, Point{x: 2, y: 1}, Point{x: 1, y: 0}],
+            rotation: Rotation::Zero,
+            box_size: 3
+        }
+    }
+    pub fn z() -> Tetromino
+    {
+        Tetromino
+        {
+            kind: TetrominoKind::Z,
+            position: Point::zero(),
+            points: [Point{x: 0, y: 0}, Point{x: 1, y: 0}, Point{x: 1, y: 1}, Point{x: 2, y: 1}],
+            rotation: Rotation::Zero,
+            box_size: 3
+        }
+    }
+    pub fn from_kind(kind: TetrominoKind) -> Tetromino
+    {
+        match kind
+        {
+            TetrominoKind::I => Tetromino::i(),
+            TetrominoKind::J => Tetromino::j(),
+            TetrominoKind::L => Tetromino::l(),
+            TetrominoKind::O => Tetromino::o(),
+            TetrominoKind::S => Tetromino::s(),
+            TetrominoKind::T => Tetromino::t(),
+            TetrominoKind::Z => Tetromino::z()
+        }
+    }
+    pub fn generate_bounds(&self) -> (Point, Point)
+    {
+        let mut top_left = Point{x: 100000, y: 100000};
+        let mut bot_right = Point{x: -100000, y: -100000};
+
+        for point in self.points.iter()
+        {
+            top_left.x = cmp::min(point.x, top_left.x);
+            bot_right.x = cmp::max(point.x, bot_right.x);          
+            top_left.y = cmp::min(point.y, top_left.y);
+            bot_right.y = cmp::max(point.y, bot_right.y);
+        }
+
+        (top_left + self.position, bot_right + self.position)
+    }
+    //Tries every kick for the rotation in order, returns the index of the kick that fit
+    pub fn rotate(&mut self, board: &Board, direction: RotationDirection) -> Option<usize>
+    {
+        let safe_points = self.points;
+        let origin = self.position;
+        let target = self.rotation.rotated(direction);
+
+        for point in self.points.iter_mut()
+        {
+            *point = match direction
+            {
+                RotationDirection::Clockwise => Point{ x: self.box_size - 1 - point.y, y: point.x },
+                RotationDirection::CounterClockwise => Point{ x: point.y, y: self.box_size - 1 - point.x },
+                RotationDirection::Half => Point{ x: self.box_size - 1 - point.x, y: self.box_size - 1 - point.y }
+            };
+        }
+
+        for (i, kick) in kicks::get_kicks(self.kind, self.rotation, target).iter().enumerate()
+        {
+            //Kick tables are written with y pointing up
+            self.position = origin + Point{ x: kick.0, y: -kick.1 };
+            if !board.check_collision(self)
+            {
+                self.rotation = target;
+                return Some(i);
+            }
+        }
+        self.points = safe_points;
+        self.position = origin;
+        None
+    }
+}
+
+mod kicks
+{
+    use super::{TetrominoKind, Rotation};
+
+    type Kicks = &'static [(i32, i32)];
+
+    const NONE: Kicks = &[(0, 0)];
+
+    const JLSTZ_0_R: Kicks = &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)];
+    const JLSTZ_R_0: Kicks = &[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)];
+    const JLSTZ_R_2: Kicks = &[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)];
+    const JLSTZ_2_R: Kicks = &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)];
+    const JLSTZ_2_L: Kicks = &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)];
+    const JLSTZ_L_2: Kicks = &[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)];
+    const JLSTZ_L_0: Kicks = &[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)];
+    const JLSTZ_0_L: Kicks = &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)];
+
+    const I_0_R: Kicks = &[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)];
+    const I_R_0: Kicks = &[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)];
+    const I_R_2: Kicks = &[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)];
+    const I_2_R: Kicks = &[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)];
+    const I_2_L: Kicks = &[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)];
+    const I_L_2: Kicks = &[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)];
+    const I_L_0: Kicks = &[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)];
+    const I_0_L: Kicks = &[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)];
+
+    //SRS has no 180 rotations, these follow the common SRS+ extension
+    const HALF_0_2: Kicks = &[(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)];
+    const HALF_2_0: Kicks = &[(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)];
+    const HALF_R_L: Kicks = &[(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)];
+    const HALF_L_R: Kicks = &[(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)];
+
+    pub fn get_kicks(kind: TetrominoKind, from: Rotation, to: Rotation) -> Kicks
+    {
+        use Rotation::*;
+        match (kind, from, to)
+        {
+            (TetrominoKind::O, _, _) => NONE,
+            (_, Zero, Two) => HALF_0_2,
+            (_, Two, Zero) => HALF_2_0,
+            (_, Right, Left) => HALF_R_L,
+            (_, Left, Right) => HALF_L_R,
+            (TetrominoKind::I, Zero, Right) => I_0_R,
+            (TetrominoKind::I, Right, Zero) => I_R_0,
+            (TetrominoKind::I, Right, Two) => I_R_2,
+            (TetrominoKind::I, Two, Right) => I_2_R,
+            (TetrominoKind::I, Two, Left) => I_2_L,
+            (TetrominoKind::I, Left, Two) => I_L_2,
+            (TetrominoKind::I, Left, Zero) => I_L_0,
+            (TetrominoKind::I, Zero, Left) => I_0_L,
+            (_, Zero, Right) => JLSTZ_0_R,
+            (_, Right, Zero) => JLSTZ_R_0,
+            (_, Right, Two) => JLSTZ_R_2,
+            (_, Two, Right) => JLSTZ_2_R,
+            (_, Two, Left) => JLSTZ_2_L,
+            (_, Left, Two) => JLSTZ_L_2,
+            (_, Left, Zero) => JLSTZ_L_0,
+            (_, Zero, Left) => JLSTZ_0_L,
+            _ => NONE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::board::Cell;
+
+    const ROTATIONS: [Rotation; 4] = [Rotation::Zero, Rotation::Right, Rotation::Two, Rotation::Left];
+    const DIRECTIONS: [RotationDirection; 3] = [RotationDirection::Clockwise, RotationDirection::CounterClockwise, RotationDirection::Half];
+    const KINDS: [TetrominoKind; 7] = [TetrominoKind::I, TetrominoKind::J, TetrominoKind::L, TetrominoKind::O, TetrominoKind::S, TetrominoKind::T, TetrominoKind::Z];
+    const ORIGIN: Point = Point{ x: 3, y: 8 };
+
+    fn piece_in_state(kind: TetrominoKind, rotation: Rotation) -> Tetromino
+    {
+        let board = Board::new(10, 20);
+        let mut piece = Tetromino::from_kind(kind);
+        piece.position = ORIGIN;
+        while piece.rotation != rotation
+        {
+            piece.rotate(&board, RotationDirection::Clockwise);
+        }
+        piece.position = ORIGIN;
+        piece
+    }
+    fn cells_after_kick(piece: &Tetromino, direction: RotationDirection, kick: (i32, i32)) -> Vec<Point>
+    {
+        let mut rotated = piece.clone();
+        rotated.rotate(&Board::new(10, 20), direction);
+        rotated.position = ORIGIN + Point{ x: kick.0, y: -kick.1 };
+        rotated.points.iter().map(|point| *point + rotated.position).collect()
+    }
+
+    #[test]
+    fn four_rotations_return_to_spawn()
+    {
+        let board = Board::new(10, 20);
+        for kind in KINDS.iter()
+        {
+            for direction in DIRECTIONS.iter()
+            {
+                let mut piece = piece_in_state(*kind, Rotation::Zero);
+                for _i in 0..4
+                {
+                    assert!(piece.rotate(&board, *direction).is_some());
+                }
+                assert_eq!(Rotation::Zero, piece.rotation);
+                assert_eq!(Tetromino::from_kind(*kind).points, piece.points);
+            }
+        }
+    }
+
+    #[test]
+    fn every_kick_is_tried_in_order()
+    {
+        for kind in KINDS.iter()
+        {
+            for from in ROTATIONS.iter()
+            {
+                for direction in DIRECTIONS.iter()
+                {
+                    let piece = piece_in_state(*kind, *from);
+                    let to = from.rotated(*direction);
+                    let table = kicks::get_kicks(*kind, *from, to);
+
+                    for (expected, kick) in table.iter().enumerate()
+                    {
+                        //Block one cell of every earlier kick that the expected kick does not use
+                        let target = cells_after_kick(&piece, *direction, *kick);
+                        let mut board = Board::new(10, 20);
+                        for earlier in table[..expected].iter()
+                        {
+                            let blocker = cells_after_kick(&piece, *direction, *earlier).into_iter().find(|cell| !target.contains(cell)).unwrap();
+                            board.set_cell(blocker.x as u32, blocker.y as u32, Cell::Occupied(piece.kind));
+                        }
+
+                        let mut rotated = piece.clone();
+                        assert_eq!(Some(expected), rotated.rotate(&board, *direction), "{:?} {:?} {:?}", kind, from, direction);
+                        assert_eq!(to, rotated.rotation);
+                        assert_eq!(ORIGIN + Point{ x: kick.0, y: -kick.1 }, rotated.position);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blocked_rotation_keeps_piece()
+    {
+        for kind in KINDS.iter()
+        {
+            for direction in DIRECTIONS.iter()
+            {
+                let piece = piece_in_state(*kind, Rotation::Zero);
+                let to = piece.rotation.rotated(*direction);
+                let mut board = Board::new(10, 20);
+                for kick in kicks::get_kicks(*kind, piece.rotation, to).iter()
+                {
+                    for cell in cells_after_kick(&piece, *direction, *kick)
+                    {
+                        board.set_cell(cell.x as u32, cell.y as u32, Cell::Occupied(piece.kind));
+                    }
+                }
+
+                let mut rotated = piece.clone();
+                assert_eq!(None, rotated.rotate(&board, *direction));
+                assert_eq!(piece.points, rotated.points);
+                assert_eq!(piece.position, rotated.position);
+                assert_eq!(Rotation::Zero, rotated.rotation);
+            }
+        }
+    }
+
+    #[test]
+    fn i_piece_kicks_off_left_wall()
+    {
+        let board = Board::new(10, 20);
+        let mut piece = piece_in_state(TetrominoKind::I, Rotation::Right);
+        piece.position = Point{ x: -2, y: 8 };
+        assert!(!board.check_collision(&piece));
+
+        assert_eq!(Some(1), piece.rotate(&board, RotationDirection::CounterClockwise));
+        assert_eq!(Point{ x: 0, y: 8 }, piece.position);
+    }
+}
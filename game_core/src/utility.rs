@@ -1,6 +1,6 @@
 use std::ops;
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq)]
 pub struct Point
 {
     pub x: i32,
@@ -21,4 +21,20 @@ impl ops::Add<Point> for Point
     {
         Point { x: self.x + rhs.x, y: self.y + rhs.y}
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub struct Color
+{
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32
+}
+impl Color
+{
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color
+    {
+        Color { r, g, b, a }
+    }
+}
@@ -1,359 +1,356 @@
-use ggez::{Context, GameResult, timer};
-use ggez::graphics::{self, DrawMode, Color };
-use ggez::event::{EventHandler, KeyCode};
-use rand::thread_rng;
-use rand::seq::SliceRandom;
-use tetromino::Tetromino;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use ggez::{Context, GameResult, graphics, timer};
+use ggez::graphics::{DrawMode, Rect};
+use game_core::{GameState, GameMode, Inputs, Cell, Tetromino, Replay, ReplayPlayer, BotPlayer};
+use game_core::utility::{Color, Point};
 use renderer::Renderer;
+use layout::Layout;
+use hud::Hud;
 use input::Input;
-use crate::utility::Point;
+use input::bindings::Action;
+use crate::scene::{Scene, Shared, Settings, Transition, Command};
+use crate::scene::pause_menu::PauseMenu;
+use crate::scene::game_over::GameOverMenu;
+use high_scores::HighScore;
 
-pub mod tetromino;
 pub mod renderer;
 pub mod input;
+pub mod layout;
+pub mod hud;
+pub mod theme;
+pub mod high_scores;
+pub mod versus;
 
-
-#[derive(Debug, Clone)]
-enum Cell
-{
-    Empty,
-    Occupied(Color)
-}
-pub struct Board
+pub struct Game
 {
-    width: u32,
-    height: u32,
-    cells: Vec<Cell>,
+    state: GameState,
+    recording: Replay,
+    playback: Option<ReplayPlayer>,
+    bot: Option<BotPlayer>,
+    //Where finished games are saved, versus games have none
+    replay_directory: Option<PathBuf>
 }
-impl Board
+impl Game
 {
-    const CELL_SPACING: f32 = 3.0;
-    const ORIGIN_OFFSET: (f32, f32) = (150.0, 15.0);
-    const CELL_SIZE: f32 = 20.0;
+    pub const REPLAY_DIRECTORY: &'static str = "replays";
 
-    fn new(width: u32, height: u32) -> Board
+    //Replays bring their own mode and rules
+    pub fn new(shared: &Shared, mode: GameMode, replay: Option<Replay>, bot: Option<BotPlayer>) -> Game
     {
-        let mut v = Vec::new();
-        for _i in 0..width*height
-        {         
-            //v.push(if i < width * (height - 1) { Cell::Empty } else { Cell::Occupied });
-            v.push(Cell::Empty);
-        }
-
-        Board{width: width, height: height, cells: v}
-    }
-    pub fn check_collision(&self, piece: &Tetromino) -> bool
-    {   
-        let (tl, br) = piece.generate_bounds();
-        if tl.x < 0 || br.x >= (self.width as i32) || br.y >= (self.height as i32)        
+        let settings = &shared.settings;
+        let state = match &replay
         {
-            return true;
-        }
-
-        for point in piece.points.iter()
-        {
-            let index = point.x + piece.position.x + ((point.y + piece.position.y) * self.width as i32);
-            if index < 0
-            {
-                continue;
-            }
-            match self.cells[index as usize]
+            Some(replay) => replay.create_state(),
+            None => 
             {
-                Cell::Occupied(_color) => { return true; }
-                _=> continue
+                let mut state = GameState::new();
+                state.set_rules(settings.rules);
+                state.set_mode(mode);
+                state
             }
+        };
+        Game 
+        { 
+            recording: Replay::new(state.seed(), *state.rules(), state.mode()),
+            playback: replay.map(ReplayPlayer::new),
+            bot,
+            replay_directory: Some(shared.replay_directory.clone()),
+            state
         }
-        false
+    }   
+    //One side of a versus game, both sides get the same seed so they are dealt the same pieces
+    fn versus(settings: &Settings, seed: u64) -> Game
+    {
+        let mut state = GameState::with_seed(seed);
+        state.set_rules(settings.rules);
+        state.set_mode(GameMode::Versus);
+        state.set_garbage_rules(settings.garbage);
+        Game { recording: Replay::new(seed, settings.rules, GameMode::Versus), playback: None, bot: None, replay_directory: None, state }
     }
-    fn clear_lines(&mut self)
-    {    
-        //Check lines
-        let mut lines_to_clear : Vec<usize> = Vec::new();
-        for y in (0..self.height).rev()
+    fn restart(&mut self, settings: &Settings)
+    {
+        self.save_recording();
+        match self.playback.take()
         {
-            let mut full_line = true;
-            for x in 0..self.width 
+            Some(player) =>
             {
-                match self.cells[(x + y * self.width) as usize]
-                {   
-                    Cell::Empty => { full_line = false; break; }                    
-                    Cell::Occupied(_color) => { continue; }
-                }
+                self.state = player.replay().create_state();
+                self.playback = Some(ReplayPlayer::new(player.replay().clone()));
             }
-            if full_line
+            None => 
             {
-                lines_to_clear.push(y as usize);
+                self.state.restart();
+                self.state.set_rules(settings.rules);
             }
         }
-        //Clear lines
-        for y in lines_to_clear.iter()
+        if let Some(bot) = &mut self.bot
         {
-            for x in 0..self.width as usize
-            {
-                self.cells[(x + y * self.width as usize)] = Cell::Empty;
-            }
+            bot.reset();
         }
-
-        if lines_to_clear.len() == 0
+        self.recording = Replay::new(self.state.seed(), *self.state.rules(), self.state.mode());
+    }
+    //Versus games can't be replayed, the garbage depends on the other player
+    fn save_recording(&mut self)
+    {
+        let directory = match &self.replay_directory
         {
-            return;
-        }
-        for line in lines_to_clear.iter().rev()
+            Some(directory) if self.playback.is_none() && !self.recording.frames.is_empty() && self.state.mode() != GameMode::Versus => directory,
+            _ => return
+        };
+        let date = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        let path = Game::replay_path(directory, self.recording.seed, date);
+        match self.recording.save(&path)
         {
-            for y in (0..*line).rev()
-            {
-                println!("moveing line {} down", y);
-                self.move_line_down(y);
-            }
+            Ok(_) => println!("Saved replay to {}", path.display()),
+            Err(e) => println!("Error: {}", e)
         }
-        
+        self.recording.frames.clear();
     }
-    fn move_line_down(&mut self, y: usize)
+    //`<seed>-<date>.replay`, counting up when games with the same seed end in the same second
+    fn replay_path(directory: &Path, seed: u64, date: u64) -> PathBuf
     {
-        //Check if line bellow is empty
-        for x in 0..self.width as usize
+        let mut path = directory.join(format!("{}-{}.replay", seed, date));
+        let mut count = 1;
+        while path.exists()
         {
-            match self.cells[x + (y + 1) * self.width as usize]
-            {
-                Cell::Occupied(_color) => { return; }
-                _=> { continue; }
-            }
+            count += 1;
+            path = directory.join(format!("{}-{}-{}.replay", seed, date, count));
         }
-        //Move line down
-        for x in 0..self.width as usize
+        path
+    }
+    fn player_inputs(input: &Input) -> Inputs
+    {
+        Inputs
         {
-            self.cells[x + (y + 1) * self.width as usize] = self.cells[x + y * self.width as usize].clone();
-            self.cells[x + y * self.width as usize] = Cell::Empty;
+            horizontal: input.get_axis(Action::MoveLeft, Action::MoveRight),
+            soft_drop: input.get_action(Action::SoftDrop),
+            rotate_cw: input.get_action_down(Action::RotateCw),
+            rotate_ccw: input.get_action_down(Action::RotateCcw),
+            rotate_180: input.get_action_down(Action::Rotate180),
+            hard_drop: input.get_action_down(Action::HardDrop),
+            hold: input.get_action_down(Action::Hold)
         }
     }
-}
-
-pub struct Game
-{
-    board: Board,
-    active_piece: Tetromino,
-    input_timer: f32,
-    tick_timer: f32,
-    tetromino_hat: [Tetromino; Game::NUM_OF_TETROMINOS],
-    next_hat: [Tetromino; Game::NUM_OF_TETROMINOS],
-    current_tetromino_index: usize,
-    input: Input,
-}
-impl Game
-{
-    const INPUT_DELAY: f32 = 0.1;
-    const TICK_DELAY: f32 = 0.5;
-    const TICK_DELAY_FAST: f32 = 0.2;
-    const NUM_OF_TETROMINOS: usize = 7;
-    const NUM_OF_NEXT_PIECES: usize = 4;
-
-    pub fn new(_context: &mut Context) -> Game
+    fn step_player(&mut self, delta_time: f32, input: &Input)
     {
-        let mut hat: [Tetromino; Game::NUM_OF_TETROMINOS] = 
-        [
-            Tetromino::i(),
-            Tetromino::j(),
-            Tetromino::l(),
-            Tetromino::o(),
-            Tetromino::s(),
-            Tetromino::t(),
-            Tetromino::z()
-        ];
-        hat.shuffle(&mut thread_rng());
-        let mut temp_hat = hat.clone();
-        temp_hat.shuffle(&mut thread_rng());
-        Game 
-        { 
-            board: Board::new(10, 20), 
-            active_piece: hat[0].clone(), 
-            input_timer: 0.0, 
-            tick_timer: 0.0,
-            tetromino_hat: hat,
-            next_hat: temp_hat,
-            current_tetromino_index: 0,
-            input: Input::new()
-        }
-    }   
-    fn get_drop_position(&self, tetromino: &Tetromino) -> Point
+        let inputs = Game::player_inputs(input);
+        self.state.step(delta_time, &inputs);
+        self.recording.record(delta_time, &inputs);
+    }
+    //The piece's points that are below the buffer
+    fn visible_points(piece: &Tetromino, buffer: u32) -> Vec<Point>
     {
-        let mut temp = tetromino.clone();
-        let mut previous_position = temp.position;
-        while !self.board.check_collision(&temp)
-        {
-            previous_position = temp.position;
-            temp.position.y += 1;
-        }
-        previous_position
+        piece.points.iter().filter(|point| point.y + piece.position.y >= buffer as i32).copied().collect()
     }
-    fn apply_piece_to_board(&mut self)
-    {        
-        for point in self.active_piece.points.iter()
+    //Fills the spacing between cells
+    fn draw_grid(renderer: &mut Renderer, layout: &Layout, color: Color)
+    {
+        let color = Renderer::to_color(color);
+        let size = layout.board_size();
+        for x in 0..=layout.columns
         {
-            let index = (point.x + self.active_piece.position.x + ((point.y + self.active_piece.position.y) * self.board.width as i32)) as usize;
-            self.board.cells[index] = Cell::Occupied(self.active_piece.color);
+            let rect = Rect::new(layout.origin.0 + x as f32 * layout.stride(), layout.origin.1, layout.cell_spacing, size.1);
+            renderer.rectangle(DrawMode::fill(), rect, color);
         }
-        self.board.clear_lines();
-
-        //New piece
-        self.current_tetromino_index = (self.current_tetromino_index + 1) % Game::NUM_OF_TETROMINOS;
-        if self.current_tetromino_index == 0
+        for y in 0..=layout.rows
         {
-            self.tetromino_hat = self.next_hat.clone();
-            self.next_hat.shuffle(&mut thread_rng());
+            let rect = Rect::new(layout.origin.0, layout.origin.1 + y as f32 * layout.stride(), size.0, layout.cell_spacing);
+            renderer.rectangle(DrawMode::fill(), rect, color);
         }
-        self.active_piece = self.tetromino_hat[self.current_tetromino_index].clone();           
     }
-}
-impl EventHandler for Game
-{
-    fn update(&mut self, context: &mut Context) -> GameResult<()>
+    //Fits the whole game view into `area`, so several games can share the screen
+    fn draw_in(&self, context: &mut Context, shared: &Shared, renderer: &mut Renderer, area: Rect)
     {
-        self.input.update(context);
-        let delta_time = timer::delta(context).as_secs_f32();
-        self.input_timer += delta_time;
-        self.tick_timer += delta_time;
+        let board = self.state.board();
+        let layout = Layout::new(board, area);
+        let preview = (layout.preview_size(), layout.preview_size());
+        let theme = shared.theme();
 
-        let previous_position = self.active_piece.position.clone();
-
-        let delay = if self.input.get_key(KeyCode::S) { Game::TICK_DELAY_FAST } else { Game::TICK_DELAY };
-        //Tick
-        if self.tick_timer > delay
-        {
-            self.tick_timer = 0.0;    
-            self.active_piece.position.y += 1;
-            if self.board.check_collision(&self.active_piece)
-            {
-                self.active_piece.position = previous_position;
-                self.apply_piece_to_board();
-            }
-        }
-        
-        let previous_position = self.active_piece.position.clone();
-        let input_direction = self.input.get_axis(KeyCode::A, KeyCode::D);
-        if  input_direction != 0 && self.input_timer > Game::INPUT_DELAY
-        {
-            self.active_piece.position.x += input_direction;
-            self.input_timer = 0.0;
-        }
-        if self.input.get_key_down(KeyCode::W)
+        //Draw board
+        renderer.draw_frame(layout.origin, layout.board_size(), &theme.frame);
+        if let Some(grid) = theme.grid
         {
-            self.active_piece.rotate(&self.board);
-            self.input_timer = 0.0;
+            Game::draw_grid(renderer, &layout, grid);
         }
-        //Collision side
-        if self.board.check_collision(&self.active_piece)
-        {
-            self.active_piece.position = previous_position;
-        }  
         
-        if self.input.get_key_down(KeyCode::Space)
+        //Draw hold piece        
+        renderer.draw_frame(layout.hold_position(), preview, &theme.frame);
+        if let Some(hold_piece) = self.state.hold_piece()
         {
-            self.active_piece.position = self.get_drop_position(&self.active_piece);
-            self.apply_piece_to_board();
+            renderer.draw_tetromino(
+                &hold_piece.points,
+                layout.hold_position(),
+                (0.0, 0.0),
+                layout.cell_size,
+                layout.cell_spacing,
+                theme.piece_color(hold_piece.kind)
+            );
         }
 
-        Ok(())
-    }
-    fn draw(&mut self, context: &mut Context) -> GameResult<()>
-    {
-        graphics::clear(context, graphics::BLACK);
-
-        //Draw board
-        Renderer::draw_frame
-        (
-            context, 
-            Board::ORIGIN_OFFSET,
-            (self.board.width as f32 * (Board::CELL_SIZE + Board::CELL_SPACING) + Board::CELL_SPACING, self.board.height as f32 * (Board::CELL_SIZE + Board::CELL_SPACING) + Board::CELL_SPACING)
-        )?;
-        
-        //Draw hold piece        
-        let size = 4.0 * (Board::CELL_SIZE + Board::CELL_SPACING) + Board::CELL_SPACING;
-        Renderer::draw_frame
-        (
-            context, 
-            (Board::ORIGIN_OFFSET.0 - size, Board::ORIGIN_OFFSET.1),
-            (size, size)
-        )?;
-
         //Draw next pieces
-        for i in 0..Game::NUM_OF_NEXT_PIECES
+        for (i, kind) in self.state.next_pieces().take(Layout::NEXT_PIECES).enumerate()
         {
-            Renderer::draw_frame
-            (
-                context, 
-                (Board::ORIGIN_OFFSET.0 + self.board.width as f32 * (Board::CELL_SIZE + Board::CELL_SPACING) + Board::CELL_SPACING, Board::ORIGIN_OFFSET.1 + size * i as f32),
-                (size, size)
-            )?;
+            renderer.draw_frame(layout.next_position(i), preview, &theme.frame);
 
-            let index = self.current_tetromino_index + i + 1;
-            
-            let next_tetromino = 
-                if index >= Game::NUM_OF_TETROMINOS
-                {
-                    &self.next_hat[(self.current_tetromino_index + i + 1) - Game::NUM_OF_TETROMINOS]
-                }
-                else
-                {
-                    &self.tetromino_hat[(self.current_tetromino_index + i + 1)]
-                };
+            let next_tetromino = Tetromino::from_kind(*kind);
 
-            Renderer::draw_tetromino(
-                context, 
+            renderer.draw_tetromino(
                 &next_tetromino.points,
-                (Board::ORIGIN_OFFSET.0 + self.board.width as f32 * (Board::CELL_SIZE + Board::CELL_SPACING) + Board::CELL_SPACING, Board::ORIGIN_OFFSET.1 + size * i as f32),
+                layout.next_position(i),
                 (0.0, 0.0),
-                Board::CELL_SIZE,
-                Board::CELL_SPACING,
-                next_tetromino.color                 
-            )?;
+                layout.cell_size,
+                layout.cell_spacing,
+                theme.piece_color(*kind)
+            );
+        }
+
+        Hud::draw(context, renderer, &layout, &self.state);
+
+        //Incoming garbage as a bar rising next to the board, in the colour the rows will have
+        let incoming = self.state.incoming_garbage().min(layout.rows);
+        if incoming > 0
+        {
+            let height = incoming as f32 * layout.stride();
+            let rect = Rect::new(layout.origin.0 - layout.cell_spacing * 3.0, layout.origin.1 + layout.board_size().1 - height, layout.cell_spacing * 2.0, height);
+            renderer.rectangle(DrawMode::fill(), rect, Renderer::to_color(theme.garbage));
         }
 
-        //Draw cells
-        for y in 0..self.board.height
+        //Draw cells, the buffer rows above the field stay hidden
+        let buffer = board.buffer();
+        for y in buffer..board.height()
         {
-            for x in 0..self.board.width
+            for x in 0..board.width()
             {
-                match self.board.cells[(x + y * self.board.width) as usize]
+                match board.get_cell(x, y)
                 {
-                    Cell::Occupied(color) => 
-                    {
-                        //TODO: operator overloading for more clean code?   caching rect or mesh?
-                        let x_pos = Board::ORIGIN_OFFSET.0 + Board::CELL_SPACING + (x as f32 * (Board::CELL_SIZE + Board::CELL_SPACING));  
-                        let y_pos = Board::ORIGIN_OFFSET.1 + Board::CELL_SPACING + (y as f32 * (Board::CELL_SIZE + Board::CELL_SPACING));  
-        
-                        let rect = graphics::Rect{ x: x_pos, y: y_pos, w: Board::CELL_SIZE, h: Board::CELL_SIZE};
-                        let square = graphics::Mesh::new_rectangle(context, DrawMode::fill(), rect, color).unwrap();
-                        graphics::draw(context, &square, (ggez::nalgebra::Point2::new(0.0, 0.0),))?;
-                    }
-                    _ => continue
+                    Cell::Occupied(kind) => renderer.draw_cell(layout.cell_position(x, y - buffer), layout.cell_size, theme.piece_color(*kind)),
+                    Cell::Garbage => renderer.draw_cell(layout.cell_position(x, y - buffer), layout.cell_size, theme.garbage),
+                    Cell::Empty => continue
                 }                
             }
         }
         //Draw active piece
-        Renderer::draw_tetromino
-        (
-            context,
-            &self.active_piece.points,
-            Board::ORIGIN_OFFSET,
-            (self.active_piece.position.x as f32, self.active_piece.position.y as f32),
-            Board::CELL_SIZE,
-            Board::CELL_SPACING,
-            self.active_piece.color
-        )?;
+        let active_piece = self.state.active_piece();
+        renderer.draw_tetromino(
+            &Game::visible_points(active_piece, buffer),
+            layout.origin,
+            (active_piece.position.x as f32, (active_piece.position.y - buffer as i32) as f32 + self.state.fall_progress()),
+            layout.cell_size,
+            layout.cell_spacing,
+            theme.piece_color(active_piece.kind)
+        );
         //Draw ghost piece
-        let mut ghost_piece: Tetromino = self.active_piece.clone();
-        ghost_piece.position = self.get_drop_position(&ghost_piece);
-        ghost_piece.color.a = 0.2;
-        Renderer::draw_tetromino(
-            context, 
-            &ghost_piece.points,
-            Board::ORIGIN_OFFSET,
-            (ghost_piece.position.x as f32, ghost_piece.position.y as f32),
-            Board::CELL_SIZE,
-            Board::CELL_SPACING,
-            ghost_piece.color                 
-        )?;
-        
-        graphics::present(context)
+        if shared.settings.ghost_piece
+        {
+            let mut ghost_piece: Tetromino = active_piece.clone();
+            ghost_piece.position = self.state.get_drop_position(&ghost_piece);
+            let mut ghost_color = theme.piece_color(ghost_piece.kind);
+            ghost_color.a = theme.ghost_alpha;
+            renderer.draw_tetromino(
+                &Game::visible_points(&ghost_piece, buffer),
+                layout.origin,
+                (ghost_piece.position.x as f32, (ghost_piece.position.y - buffer as i32) as f32),
+                layout.cell_size,
+                layout.cell_spacing,
+                ghost_color
+            );
+        }
     }
-}
\ No newline at end of file
+}
+impl Scene for Game
+{
+    fn update(&mut self, context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        let input = &shared.input;
+        if input.get_action_down(Action::Pause)
+        {
+            return Ok(Transition::Push(Box::new(PauseMenu::new())));
+        }
+        if input.get_action_down(Action::Restart)
+        {
+            self.restart(&shared.settings);
+        }
+        if self.state.is_game_over()
+        {
+            return Ok(Transition::None);
+        }
+
+        let delta_time = timer::delta(context).as_secs_f32();
+        if let Some(player) = &mut self.playback
+        {
+            player.advance(delta_time, &mut self.state);
+        }
+        else if let Some(bot) = &mut self.bot
+        {
+            //The bot picks inputs per logical frame, so record each frame on its own
+            let recording = &mut self.recording;
+            self.state.step_with(delta_time, |state|
+            {
+                let inputs = bot.next_inputs(state);
+                recording.record(GameState::FRAME_TIME as f32, &inputs);
+                inputs
+            });
+        }
+        else
+        {
+            self.step_player(delta_time, &shared.input);
+        }
+        if !self.state.is_game_over()
+        {
+            return Ok(Transition::None);
+        }
+
+        self.save_recording();
+        let entry = if self.playback.is_none() && self.bot.is_none() { HighScore::from_game(&self.state) } else { None };
+        let rank = entry.and_then(|entry| shared.high_scores.insert(self.state.mode(), entry));
+        if rank.is_some()
+        {
+            if let Err(e) = shared.high_scores.save()
+            {
+                println!("Error: could not save high scores, {}", e);
+            }
+        }
+        Ok(Transition::Push(Box::new(GameOverMenu::new(&self.state, &shared.high_scores, rank))))
+    }
+    fn on_return(&mut self, shared: &mut Shared, command: Option<Command>)
+    {
+        if command == Some(Command::Restart)
+        {
+            self.restart(&shared.settings);
+        }
+    }
+    fn on_exit(&mut self)
+    {
+        self.save_recording();
+    }
+    fn draw(&mut self, context: &mut Context, shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        self.draw_in(context, shared, renderer, graphics::screen_coordinates(context));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn replays_never_overwrite_each_other()
+    {
+        let directory = std::env::temp_dir().join(format!("tetris-replays-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        let first = Game::replay_path(&directory, 7, 100);
+        assert_eq!(directory.join("7-100.replay"), first);
+        fs::write(&first, "").unwrap();
+        let second = Game::replay_path(&directory, 7, 100);
+        assert_eq!(directory.join("7-100-2.replay"), second);
+        fs::write(&second, "").unwrap();
+        assert_eq!(directory.join("7-100-3.replay"), Game::replay_path(&directory, 7, 100));
+        assert_eq!(directory.join("7-101.replay"), Game::replay_path(&directory, 7, 101));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
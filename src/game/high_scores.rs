@@ -0,0 +1,329 @@
+use std::{fmt, fs, io};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use game_core::{GameState, GameMode, GameOver};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighScore
+{
+    pub score: u32,
+    pub lines: u32,
+    pub pieces: u32,
+    //Logical frames the game took
+    pub frames: u64,
+    pub seed: u64,
+    //Seconds since the Unix epoch
+    pub date: u64
+}
+impl HighScore
+{
+    //None for games the mode doesn't rank, like a Sprint that never reached its goal
+    pub fn from_game(state: &GameState) -> Option<HighScore>
+    {
+        let completed = state.game_over() == Some(GameOver::Completed);
+        if !state.is_game_over() || (ScoreBoard::ranks_by_time(state.mode()) && !completed)
+        {
+            return None;
+        }
+        let date = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
+        let score = state.score();
+        Some(HighScore { score: score.score(), lines: score.lines(), pieces: state.pieces_placed(), frames: state.frame(), seed: state.seed(), date })
+    }
+    pub fn seconds(&self) -> f64
+    {
+        self.frames as f64 * GameState::FRAME_TIME
+    }
+    //YYYY-MM-DD in UTC
+    pub fn format_date(&self) -> String
+    {
+        //Days to civil date, from Howard Hinnant's date algorithms
+        let days = (self.date / 86400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+#[derive(Debug)]
+pub enum ScoreError
+{
+    Io(io::Error),
+    //Written by a newer version, left alone for it
+    UnsupportedVersion(String),
+    //Not a high score file at all
+    Corrupt(String)
+}
+impl fmt::Display for ScoreError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            ScoreError::Io(e) => write!(f, "could not read high scores: {}", e),
+            ScoreError::UnsupportedVersion(header) => write!(f, "unsupported high score version '{}'", header),
+            ScoreError::Corrupt(header) => write!(f, "not a high score file, it starts with '{}'", header)
+        }
+    }
+}
+impl std::error::Error for ScoreError {}
+impl From<io::Error> for ScoreError
+{
+    fn from(e: io::Error) -> ScoreError
+    {
+        ScoreError::Io(e)
+    }
+}
+
+//A leaderboard per mode, kept in a text file next to the player's other data
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoreBoard
+{
+    path: Option<PathBuf>,
+    tables: HashMap<GameMode, Vec<HighScore>>
+}
+impl ScoreBoard
+{
+    pub const FILE_NAME: &'static str = "high_scores.txt";
+    pub const MAX_ENTRIES: usize = 10;
+    const HEADER: &'static str = "tetris-scores 1";
+    const HEADER_NAME: &'static str = "tetris-scores";
+
+    //Sprint is a race, every other mode is ranked by score
+    pub fn ranks_by_time(mode: GameMode) -> bool
+    {
+        mode == GameMode::Sprint
+    }
+    fn is_better(mode: GameMode, entry: &HighScore, other: &HighScore) -> bool
+    {
+        if ScoreBoard::ranks_by_time(mode) { entry.frames < other.frames } else { entry.score > other.score }
+    }
+    //Never fails, a missing file starts an empty board and a corrupt one is moved aside to `<path>.corrupt`.
+    //A file that can't be read or comes from a newer version is left as it is and this session's scores aren't saved
+    pub fn load<P: AsRef<Path>>(path: P) -> ScoreBoard
+    {
+        let path = path.as_ref();
+        let result = match fs::read_to_string(path)
+        {
+            Ok(text) => ScoreBoard::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok((ScoreBoard::default(), 0)),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => Err(ScoreError::Corrupt("invalid text".to_string())),
+            Err(e) => Err(ScoreError::Io(e))
+        };
+        let mut board = match result
+        {
+            Ok((board, skipped)) =>
+            {
+                if skipped > 0
+                {
+                    println!("Skipped {} unreadable high score entries in {}", skipped, path.display());
+                }
+                board
+            }
+            Err(e @ ScoreError::Io(_)) | Err(e @ ScoreError::UnsupportedVersion(_)) =>
+            {
+                println!("High scores won't be saved this session, {}", e);
+                return ScoreBoard::default();
+            }
+            Err(e) =>
+            {
+                let backup = path.with_extension("corrupt");
+                match fs::rename(path, &backup)
+                {
+                    Ok(_) => println!("Starting new high scores, {}. The old file was moved to {}", e, backup.display()),
+                    Err(_) => println!("Starting new high scores, {}", e)
+                }
+                ScoreBoard::default()
+            }
+        };
+        board.path = Some(path.to_path_buf());
+        board
+    }
+    //Lines that don't parse are skipped and counted, a wrong header fails the whole file
+    pub fn parse(text: &str) -> Result<(ScoreBoard, usize), ScoreError>
+    {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or("");
+        if header != ScoreBoard::HEADER
+        {
+            let versioned = header.strip_prefix(ScoreBoard::HEADER_NAME).is_some_and(|version| version.trim().parse::<u32>().is_ok());
+            return Err(if versioned { ScoreError::UnsupportedVersion(header.to_string()) } else { ScoreError::Corrupt(header.to_string()) });
+        }
+        let mut board = ScoreBoard::default();
+        let mut skipped = 0;
+        for line in lines.filter(|line| !line.trim().is_empty())
+        {
+            match ScoreBoard::parse_entry(line)
+            {
+                Some((mode, entry)) => { board.insert(mode, entry); }
+                None => skipped += 1
+            }
+        }
+        Ok((board, skipped))
+    }
+    //<mode> <score> <lines> <pieces> <frames> <seed> <date>
+    fn parse_entry(line: &str) -> Option<(GameMode, HighScore)>
+    {
+        match line.split_whitespace().collect::<Vec<&str>>().as_slice()
+        {
+            [mode, score, lines, pieces, frames, seed, date] => Some((GameMode::from_name(mode)?, HighScore
+            {
+                score: score.parse().ok()?,
+                lines: lines.parse().ok()?,
+                pieces: pieces.parse().ok()?,
+                frames: frames.parse().ok()?,
+                seed: seed.parse().ok()?,
+                date: date.parse().ok()?
+            })),
+            _ => None
+        }
+    }
+    //Written to a temporary file first so a crash mid write can't lose the old scores
+    pub fn save(&self) -> io::Result<()>
+    {
+        let path = match &self.path
+        {
+            Some(path) => path,
+            None => return Ok(())
+        };
+        if let Some(directory) = path.parent()
+        {
+            fs::create_dir_all(directory)?;
+        }
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, self.to_string())?;
+        fs::rename(&temporary, path)
+    }
+    //Best first
+    pub fn table(&self, mode: GameMode) -> &[HighScore]
+    {
+        self.tables.get(&mode).map(|table| table.as_slice()).unwrap_or(&[])
+    }
+    //Keeps the mode's table sorted, returns the rank the entry got if it made the list
+    pub fn insert(&mut self, mode: GameMode, entry: HighScore) -> Option<usize>
+    {
+        let table = self.tables.entry(mode).or_default();
+        let rank = table.iter().position(|other| ScoreBoard::is_better(mode, &entry, other)).unwrap_or(table.len());
+        if rank >= ScoreBoard::MAX_ENTRIES
+        {
+            return None;
+        }
+        table.insert(rank, entry);
+        table.truncate(ScoreBoard::MAX_ENTRIES);
+        Some(rank)
+    }
+}
+impl fmt::Display for ScoreBoard
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "{}", ScoreBoard::HEADER)?;
+        for mode in GameMode::ALL.iter()
+        {
+            for entry in self.table(*mode)
+            {
+                writeln!(f, "{} {} {} {} {} {} {}", mode.name(), entry.score, entry.lines, entry.pieces, entry.frames, entry.seed, entry.date)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn entry(score: u32, frames: u64) -> HighScore
+    {
+        HighScore { score, lines: 0, pieces: 0, frames, seed: 1, date: 0 }
+    }
+
+    #[test]
+    fn keeps_the_best_scores_in_order()
+    {
+        let mut board = ScoreBoard::default();
+        for score in 1..=ScoreBoard::MAX_ENTRIES as u32
+        {
+            assert_eq!(Some(0), board.insert(GameMode::Marathon, entry(score * 10, 0)));
+        }
+        assert_eq!(None, board.insert(GameMode::Marathon, entry(5, 0)));
+        assert_eq!(Some(1), board.insert(GameMode::Marathon, entry(95, 0)));
+        let table = board.table(GameMode::Marathon);
+        assert_eq!(ScoreBoard::MAX_ENTRIES, table.len());
+        assert_eq!(vec![100, 95, 90], table.iter().take(3).map(|entry| entry.score).collect::<Vec<u32>>());
+        assert_eq!(20, table.last().unwrap().score);
+
+        //Sprint ranks the fastest first, whatever the score
+        assert_eq!(Some(0), board.insert(GameMode::Sprint, entry(10, 3000)));
+        assert_eq!(Some(0), board.insert(GameMode::Sprint, entry(5, 2000)));
+        assert!(board.table(GameMode::Ultra).is_empty());
+    }
+
+    #[test]
+    fn round_trips_and_recovers_from_bad_lines()
+    {
+        let mut board = ScoreBoard::default();
+        board.insert(GameMode::Sprint, HighScore { score: 1200, lines: 40, pieces: 100, frames: 5400, seed: 42, date: 1_700_000_000 });
+        board.insert(GameMode::Ultra, entry(5000, 7200));
+        let (parsed, skipped) = ScoreBoard::parse(&board.to_string()).unwrap();
+        assert_eq!((board.clone(), 0), (parsed, skipped));
+
+        let damaged = format!("{}Zen 1 2\nBlitz 1 2 3 4 5 6\nUltra x 0 0 0 0 0\n", board);
+        let (parsed, skipped) = ScoreBoard::parse(&damaged).unwrap();
+        assert_eq!((board, 3), (parsed, skipped));
+        assert!(matches!(ScoreBoard::parse("tetris-scores 99\n"), Err(ScoreError::UnsupportedVersion(_))));
+        assert!(matches!(ScoreBoard::parse(""), Err(ScoreError::Corrupt(_))));
+        assert!(matches!(ScoreBoard::parse("Sprint 1 2 3 4 5 6\n"), Err(ScoreError::Corrupt(_))));
+    }
+
+    fn scratch_directory(name: &str) -> PathBuf
+    {
+        let directory = std::env::temp_dir().join(format!("tetris-scores-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn only_corrupt_files_are_moved_aside()
+    {
+        let directory = scratch_directory("load");
+        let corrupt = directory.join("corrupt.txt");
+        fs::write(&corrupt, "garbage\n").unwrap();
+        let board = ScoreBoard::load(&corrupt);
+        assert_eq!(Some(corrupt.clone()), board.path);
+        assert!(!corrupt.exists() && corrupt.with_extension("corrupt").exists());
+
+        //A newer version's scores stay where they are and nothing overwrites them
+        let newer = directory.join("newer.txt");
+        fs::write(&newer, "tetris-scores 2\nSprint 1 2 3 4 5 6 7\n").unwrap();
+        let mut board = ScoreBoard::load(&newer);
+        assert_eq!(None, board.path);
+        board.insert(GameMode::Ultra, entry(10, 0));
+        board.save().unwrap();
+        assert_eq!("tetris-scores 2\nSprint 1 2 3 4 5 6 7\n", fs::read_to_string(&newer).unwrap());
+        assert!(!newer.with_extension("corrupt").exists());
+
+        //So do files that can't be read, here a directory in the file's place
+        let unreadable = directory.join("unreadable.txt");
+        fs::create_dir(&unreadable).unwrap();
+        assert_eq!(None, ScoreBoard::load(&unreadable).path);
+        assert!(unreadable.is_dir() && !unreadable.with_extension("corrupt").exists());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn formats_dates()
+    {
+        assert_eq!("1970-01-01", entry(0, 0).format_date());
+        assert_eq!("2023-11-14", HighScore { date: 1_700_000_000, ..entry(0, 0) }.format_date());
+        assert_eq!("2000-02-29", HighScore { date: 951_782_400, ..entry(0, 0) }.format_date());
+    }
+}
@@ -0,0 +1,108 @@
+use ggez::Context;
+use game_core::{GameState, ClearResult, TSpin, Metric};
+use super::layout::Layout;
+use super::renderer::Renderer;
+
+//Text around the board, labels for the hold and next frames and the stats of the current game
+pub struct Hud;
+impl Hud
+{
+    pub fn draw(context: &mut Context, renderer: &mut Renderer, layout: &Layout, state: &GameState)
+    {
+        let hold = layout.hold_position();
+        renderer.draw_text(context, "HOLD", (hold.0, hold.1 - layout.label_height()));
+        let next = layout.next_position(0);
+        renderer.draw_text(context, "NEXT", (next.0, next.1 - layout.label_height()));
+        renderer.draw_text(context, &Hud::stats(state), layout.stats_position());
+    }
+    //The mode's metrics, then the combo and the last clear
+    pub fn stats(state: &GameState) -> String
+    {
+        let mode = state.mode();
+        let mut text = mode.name().to_uppercase();
+        for metric in mode.metrics()
+        {
+            text.push('\n');
+            text.push_str(&Hud::metric(state, *metric));
+        }
+        let score = state.score();
+        text.push_str(&format!("\nCombo: {}", score.combo().max(0)));
+        if score.back_to_back()
+        {
+            text.push_str("\nB2B");
+        }
+        if let Some(clear) = state.last_clear()
+        {
+            text.push('\n');
+            text.push_str(&Hud::clear_name(clear));
+        }
+        text
+    }
+    pub fn metric(state: &GameState, metric: Metric) -> String
+    {
+        let score = state.score();
+        let mode = state.mode();
+        match metric
+        {
+            Metric::Score => format!("Score: {}", score.score()),
+            Metric::Level => format!("Level: {}", score.level()),
+            Metric::Lines => format!("Lines: {}", score.lines()),
+            Metric::LinesLeft => format!("Lines left: {}", mode.lines_left(state).unwrap_or(0)),
+            Metric::Time => format!("Time: {}", Hud::format_time(state.elapsed())),
+            Metric::TimeLeft => format!("Time left: {}", Hud::format_time(mode.time_left(state).unwrap_or(0.0))),
+            Metric::PiecesPerSecond =>
+            {
+                let elapsed = state.elapsed();
+                format!("PPS: {:.2}", if elapsed > 0.0 { state.pieces_placed() as f64 / elapsed } else { 0.0 })
+            }
+            Metric::LinesSent => format!("Sent: {}", state.lines_sent())
+        }
+    }
+    //m:ss.cc
+    pub fn format_time(seconds: f64) -> String
+    {
+        let hundredths = (seconds * 100.0) as u64;
+        format!("{}:{:02}.{:02}", hundredths / 6000, hundredths / 100 % 60, hundredths % 100)
+    }
+    //Name of a clear as shown in the HUD, e.g. "T-Spin Double"
+    pub fn clear_name(result: &ClearResult) -> String
+    {
+        let spin = match result.t_spin
+        {
+            TSpin::None => "",
+            TSpin::Mini => "T-Spin Mini ",
+            TSpin::Full => "T-Spin "
+        };
+        let lines = ["", "Single", "Double", "Triple", "Tetris"][result.lines().min(4) as usize];
+        let name = format!("{}{}", spin, lines);
+        if result.perfect_clear
+        {
+            format!("{}\nPerfect Clear", name.trim_end())
+        }
+        else
+        {
+            name.trim_end().to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use game_core::GameMode;
+
+    #[test]
+    fn formats_time_and_clears()
+    {
+        let mut state = GameState::with_seed(1);
+        state.set_mode(GameMode::Sprint);
+        assert!(Hud::stats(&state).starts_with("SPRINT\nLines left: 40\nTime: 0:00.00"));
+        assert_eq!("0:00.00", Hud::format_time(0.0));
+        assert_eq!("1:05.25", Hud::format_time(65.25));
+        let clear = ClearResult { rows: vec![18, 19], t_spin: TSpin::Full, perfect_clear: false };
+        assert_eq!("T-Spin Double", Hud::clear_name(&clear));
+        assert_eq!("T-Spin Mini", Hud::clear_name(&ClearResult { rows: Vec::new(), t_spin: TSpin::Mini, perfect_clear: false }));
+        assert_eq!("Tetris\nPerfect Clear", Hud::clear_name(&ClearResult { rows: vec![16, 17, 18, 19], t_spin: TSpin::None, perfect_clear: true }));
+    }
+}
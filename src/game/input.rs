@@ -1,95 +1,122 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use ggez::{input, timer, Context};
-use ggez::event::KeyCode;
+use ggez::event::{Button, Axis};
+use bindings::{Action, Binding, Bindings};
 
-struct KeyData
+pub mod bindings;
+
+struct ActionData
 {
-    key_code: KeyCode,
     down_frame: usize,
+    down_time: f32,
     held: bool
 }
-impl KeyData
+impl ActionData
 {
-    fn new(key_code: KeyCode) -> KeyData
+    fn new() -> ActionData
     {
-        KeyData
+        ActionData
         {
-            key_code: key_code,
             down_frame: 0,
+            down_time: 0.0,
             held: false
         }
     }
 }
 pub struct Input
 {
-    key_data: [KeyData; Input::NUM_KEYS],
-    key_map: HashMap<KeyCode, usize>,
-    latest_frame: usize
+    bindings: Bindings,
+    action_data: Vec<ActionData>,
+    buttons_held: HashSet<Button>,
+    axes: HashMap<Axis, f32>,
+    latest_frame: usize,
+    latest_time: f32
 }
 impl Input
 {
-    const NUM_KEYS: usize = 5;
+    const AXIS_DEAD_ZONE: f32 = 0.5;
 
-    pub fn new() -> Input
+    pub fn new(bindings: Bindings) -> Input
     {
-        let key_data =
-        [
-            KeyData::new(KeyCode::A),
-            KeyData::new(KeyCode::D),
-            KeyData::new(KeyCode::W),
-            KeyData::new(KeyCode::S),
-            KeyData::new(KeyCode::Space)
-        ];
-        let mut key_map: HashMap<KeyCode, usize> = HashMap::new();
-        for i in 0..Input::NUM_KEYS
+        Input
         {
-            key_map.insert(key_data[i].key_code, i);
+            bindings,
+            action_data: Action::ALL.iter().map(|_| ActionData::new()).collect(),
+            buttons_held: HashSet::new(),
+            axes: HashMap::new(),
+            latest_frame: 0,
+            latest_time: 0.0
         }
-
-        Input
+    }
+    pub fn on_button(&mut self, button: Button, pressed: bool)
+    {
+        if pressed
+        {
+            self.buttons_held.insert(button);
+        }
+        else
+        {
+            self.buttons_held.remove(&button);
+        }
+    }
+    pub fn on_axis(&mut self, axis: Axis, value: f32)
+    {
+        self.axes.insert(axis, value);
+    }
+    fn is_binding_active(&self, context: &Context, binding: &Binding) -> bool
+    {
+        match binding
         {
-            key_data, 
-            key_map,
-            latest_frame: 0
+            Binding::Key(key_code) => input::keyboard::is_key_pressed(context, *key_code),
+            Binding::Button(button) => self.buttons_held.contains(button),
+            Binding::Axis(axis, sign) => self.axes.get(axis).is_some_and(|value| value * sign > Input::AXIS_DEAD_ZONE)
         }
     }
     pub fn update(&mut self, context: &mut Context)
     { 
         self.latest_frame = timer::ticks(context);
+        self.latest_time = timer::time_since_start(context).as_secs_f32();
 
-        for i in 0..Input::NUM_KEYS
+        for action in Action::ALL.iter()
         {
-            let pressed = input::keyboard::is_key_pressed(context, self.key_data[i].key_code);
-            if pressed && !self.key_data[i].held
+            let pressed = self.bindings.get(*action).iter().any(|binding| self.is_binding_active(context, binding));
+            let data = &mut self.action_data[action.index()];
+            if pressed && !data.held
             {
-                self.key_data[i].held = true;
-                self.key_data[i].down_frame = self.latest_frame;
+                data.held = true;
+                data.down_frame = self.latest_frame;
+                data.down_time = self.latest_time;
             }
             else if !pressed
             {
-                self.key_data[i].held = false;
+                data.held = false;
             }
         }       
     }
-    pub fn get_key(&self, key_code: KeyCode) -> bool
+    pub fn get_action(&self, action: Action) -> bool
     {
-        self.key_data[*self.key_map.get(&key_code).unwrap()].held
+        self.action_data[action.index()].held
     }
-    pub fn get_key_down(&self, key_code: KeyCode) -> bool
+    pub fn get_action_down(&self, action: Action) -> bool
     {
-        self.key_data[*self.key_map.get(&key_code).unwrap()].down_frame == self.latest_frame
+        let data = &self.action_data[action.index()];
+        data.held && data.down_frame == self.latest_frame
     }
-    pub fn get_axis(&self, left: KeyCode, right: KeyCode) -> i32
+    //Seconds since the action was pressed, None if it is not held
+    pub fn get_held_time(&self, action: Action) -> Option<f32>
     {
-        let mut direction = 0;
-        if self.get_key(left)
-        {
-            direction += -1;
-        }
-        if self.get_key(right)
+        let data = &self.action_data[action.index()];
+        if data.held { Some(self.latest_time - data.down_time) } else { None }
+    }
+    //When both actions are held the last pressed one wins
+    pub fn get_axis(&self, left: Action, right: Action) -> i32
+    {
+        match (self.get_held_time(left), self.get_held_time(right))
         {
-            direction += 1;
+            (Some(left_time), Some(right_time)) => if right_time <= left_time { 1 } else { -1 },
+            (Some(_), None) => -1,
+            (None, Some(_)) => 1,
+            (None, None) => 0
         }
-        direction
     }
 }
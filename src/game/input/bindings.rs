@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::{fmt, fs, io};
+use std::path::Path;
+use ggez::event::{KeyCode, Button, Axis};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action
+{
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    Hold,
+    Pause,
+    Restart,
+    MenuUp,
+    MenuDown,
+    Confirm,
+    Back,
+    DebugOverlay
+}
+impl Action
+{
+    pub const ALL: [Action; 15] =
+    [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateCw,
+        Action::RotateCcw,
+        Action::Rotate180,
+        Action::Hold,
+        Action::Pause,
+        Action::Restart,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::Confirm,
+        Action::Back,
+        Action::DebugOverlay
+    ];
+
+    pub fn index(self) -> usize
+    {
+        Action::ALL.iter().position(|action| *action == self).unwrap()
+    }
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::SoftDrop => "soft_drop",
+            Action::HardDrop => "hard_drop",
+            Action::RotateCw => "rotate_cw",
+            Action::RotateCcw => "rotate_ccw",
+            Action::Rotate180 => "rotate_180",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::MenuUp => "menu_up",
+            Action::MenuDown => "menu_down",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::DebugOverlay => "debug_overlay"
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Action>
+    {
+        Action::ALL.iter().find(|action| action.name() == name).cloned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding
+{
+    Key(KeyCode),
+    Button(Button),
+    //Active when the axis is pushed past the dead zone in the direction of the sign
+    Axis(Axis, f32)
+}
+impl Binding
+{
+    //"Space", "Button:South" or "Axis:LeftStickX-"
+    pub fn parse(name: &str) -> Result<Binding, InputError>
+    {
+        if let Some(button) = name.strip_prefix("Button:")
+        {
+            return button_from_name(button).map(Binding::Button).ok_or_else(|| InputError::UnknownButton(name.to_string()));
+        }
+        if let Some(axis) = name.strip_prefix("Axis:")
+        {
+            let (axis, sign) = 
+                if let Some(axis) = axis.strip_suffix('+') { (axis, 1.0) }
+                else if let Some(axis) = axis.strip_suffix('-') { (axis, -1.0) }
+                else { return Err(InputError::UnknownAxis(name.to_string())); };
+            return axis_from_name(axis).map(|axis| Binding::Axis(axis, sign)).ok_or_else(|| InputError::UnknownAxis(name.to_string()));
+        }
+        key_from_name(name).map(Binding::Key).ok_or_else(|| InputError::UnknownKey(name.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError
+{
+    Io(io::Error),
+    Parse(String),
+    UnknownAction(String),
+    UnknownKey(String),
+    UnknownButton(String),
+    UnknownAxis(String)
+}
+impl fmt::Display for InputError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            InputError::Io(e) => write!(f, "could not read bindings: {}", e),
+            InputError::Parse(e) => write!(f, "could not parse bindings: {}", e),
+            InputError::UnknownAction(name) => write!(f, "unknown action '{}'", name),
+            InputError::UnknownKey(name) => write!(f, "unknown key '{}'", name),
+            InputError::UnknownButton(name) => write!(f, "unknown gamepad button '{}'", name),
+            InputError::UnknownAxis(name) => write!(f, "unknown gamepad axis '{}'", name)
+        }
+    }
+}
+impl std::error::Error for InputError {}
+impl From<io::Error> for InputError
+{
+    fn from(e: io::Error) -> InputError
+    {
+        InputError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bindings
+{
+    map: HashMap<Action, Vec<Binding>>
+}
+impl Bindings
+{
+    //Actions missing from the file keep their default bindings
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Bindings, InputError>
+    {
+        Bindings::parse(&fs::read_to_string(path)?)
+    }
+    pub fn parse(text: &str) -> Result<Bindings, InputError>
+    {
+        Bindings::parse_over(text, Bindings::default())
+    }
+    //Like load, with the actions missing from the file keeping the bindings in `defaults`
+    pub fn load_over<P: AsRef<Path>>(path: P, defaults: Bindings) -> Result<Bindings, InputError>
+    {
+        Bindings::parse_over(&fs::read_to_string(path)?, defaults)
+    }
+    pub fn parse_over(text: &str, defaults: Bindings) -> Result<Bindings, InputError>
+    {
+        let table: HashMap<String, Vec<String>> = toml::from_str(text).map_err(|e| InputError::Parse(e.to_string()))?;
+        let mut bindings = defaults;
+        for (name, names) in table.iter()
+        {
+            let action = Action::from_name(name).ok_or_else(|| InputError::UnknownAction(name.clone()))?;
+            let parsed = names.iter().map(|name| Binding::parse(name)).collect::<Result<Vec<Binding>, InputError>>()?;
+            bindings.map.insert(action, parsed);
+        }
+        Ok(bindings)
+    }
+    pub fn get(&self, action: Action) -> &[Binding]
+    {
+        self.map.get(&action).map(|bindings| bindings.as_slice()).unwrap_or(&[])
+    }
+    //Keyboard halves for two players sharing one keyboard, only the actions used in a game.
+    //Gamepads are handed out to the players in the order they are first used
+    pub fn versus(player: usize) -> Bindings
+    {
+        let mut map = HashMap::new();
+        let gamepad = [Binding::Button(Button::DPadLeft), Binding::Button(Button::DPadRight), Binding::Button(Button::DPadDown), Binding::Button(Button::DPadUp), Binding::Button(Button::East), Binding::Button(Button::South), Binding::Button(Button::North), Binding::Button(Button::LeftTrigger)];
+        let keys = if player == 0
+        {
+            [KeyCode::A, KeyCode::D, KeyCode::S, KeyCode::Space, KeyCode::W, KeyCode::Q, KeyCode::E, KeyCode::LShift]
+        }
+        else
+        {
+            [KeyCode::Left, KeyCode::Right, KeyCode::Down, KeyCode::RControl, KeyCode::Up, KeyCode::Period, KeyCode::Slash, KeyCode::RShift]
+        };
+        let actions = [Action::MoveLeft, Action::MoveRight, Action::SoftDrop, Action::HardDrop, Action::RotateCw, Action::RotateCcw, Action::Rotate180, Action::Hold];
+        for ((action, key), button) in actions.iter().zip(keys.iter()).zip(gamepad.iter())
+        {
+            map.insert(*action, vec![Binding::Key(*key), *button]);
+        }
+        Bindings { map }
+    }
+}
+impl Default for Bindings
+{
+    fn default() -> Bindings
+    {
+        let mut map = HashMap::new();
+        map.insert(Action::MoveLeft, vec![Binding::Key(KeyCode::A), Binding::Key(KeyCode::Left), Binding::Button(Button::DPadLeft), Binding::Axis(Axis::LeftStickX, -1.0)]);
+        map.insert(Action::MoveRight, vec![Binding::Key(KeyCode::D), Binding::Key(KeyCode::Right), Binding::Button(Button::DPadRight), Binding::Axis(Axis::LeftStickX, 1.0)]);
+        map.insert(Action::SoftDrop, vec![Binding::Key(KeyCode::S), Binding::Key(KeyCode::Down), Binding::Button(Button::DPadDown), Binding::Axis(Axis::LeftStickY, -1.0)]);
+        map.insert(Action::HardDrop, vec![Binding::Key(KeyCode::Space), Binding::Button(Button::DPadUp)]);
+        map.insert(Action::RotateCw, vec![Binding::Key(KeyCode::W), Binding::Key(KeyCode::Up), Binding::Button(Button::East)]);
+        map.insert(Action::RotateCcw, vec![Binding::Key(KeyCode::Q), Binding::Key(KeyCode::Z), Binding::Button(Button::South)]);
+        map.insert(Action::Rotate180, vec![Binding::Key(KeyCode::E), Binding::Button(Button::North)]);
+        map.insert(Action::Hold, vec![Binding::Key(KeyCode::LShift), Binding::Key(KeyCode::C), Binding::Button(Button::LeftTrigger), Binding::Button(Button::RightTrigger)]);
+        map.insert(Action::Pause, vec![Binding::Key(KeyCode::Escape), Binding::Button(Button::Start)]);
+        map.insert(Action::Restart, vec![Binding::Key(KeyCode::R), Binding::Button(Button::Select)]);
+        map.insert(Action::MenuUp, vec![Binding::Key(KeyCode::W), Binding::Key(KeyCode::Up), Binding::Button(Button::DPadUp), Binding::Axis(Axis::LeftStickY, 1.0)]);
+        map.insert(Action::MenuDown, vec![Binding::Key(KeyCode::S), Binding::Key(KeyCode::Down), Binding::Button(Button::DPadDown), Binding::Axis(Axis::LeftStickY, -1.0)]);
+        map.insert(Action::Confirm, vec![Binding::Key(KeyCode::Return), Binding::Key(KeyCode::Space), Binding::Button(Button::South)]);
+        map.insert(Action::Back, vec![Binding::Key(KeyCode::Escape), Binding::Key(KeyCode::Back), Binding::Button(Button::East)]);
+        map.insert(Action::DebugOverlay, vec![Binding::Key(KeyCode::F3)]);
+        Bindings { map }
+    }
+}
+
+macro_rules! from_name
+{
+    ($name:expr, $type:ident, [$($variant:ident),*]) =>
+    {
+        match $name
+        {
+            $(stringify!($variant) => Some($type::$variant),)*
+            _ => None
+        }
+    }
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode>
+{
+    from_name!(name, KeyCode,
+    [
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+        Left, Right, Up, Down, Space, Return, Escape, Tab, Back,
+        LShift, RShift, LControl, RControl, LAlt, RAlt,
+        Comma, Period, Slash, Semicolon,
+        F1, F2, F3
+    ])
+}
+fn button_from_name(name: &str) -> Option<Button>
+{
+    from_name!(name, Button,
+    [
+        South, East, North, West, C, Z,
+        LeftTrigger, LeftTrigger2, RightTrigger, RightTrigger2,
+        Select, Start, Mode, LeftThumb, RightThumb,
+        DPadUp, DPadDown, DPadLeft, DPadRight
+    ])
+}
+fn axis_from_name(name: &str) -> Option<Axis>
+{
+    from_name!(name, Axis, [LeftStickX, LeftStickY, LeftZ, RightStickX, RightStickY, RightZ, DPadX, DPadY])
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn parses_keys_buttons_and_axes()
+    {
+        let bindings = Bindings::parse("hold = [\"C\", \"Button:West\", \"Axis:RightStickY+\"]").unwrap();
+        assert_eq!(&[Binding::Key(KeyCode::C), Binding::Button(Button::West), Binding::Axis(Axis::RightStickY, 1.0)], bindings.get(Action::Hold));
+        assert_eq!(Bindings::default().get(Action::Pause), bindings.get(Action::Pause));
+    }
+
+    #[test]
+    fn unknown_names_are_errors()
+    {
+        assert!(matches!(Bindings::parse("hold = [\"NotAKey\"]"), Err(InputError::UnknownKey(_))));
+        assert!(matches!(Bindings::parse("hold = [\"Button:Nope\"]"), Err(InputError::UnknownButton(_))));
+        assert!(matches!(Bindings::parse("hold = [\"Axis:LeftStickX\"]"), Err(InputError::UnknownAxis(_))));
+        assert!(matches!(Bindings::parse("jump = [\"Space\"]"), Err(InputError::UnknownAction(_))));
+        assert!(matches!(Bindings::parse("hold = 3"), Err(InputError::Parse(_))));
+    }
+
+    #[test]
+    fn versus_players_use_different_keys()
+    {
+        let (first, second) = (Bindings::versus(0), Bindings::versus(1));
+        let keys = |bindings: &Bindings, action: Action| bindings.get(action).iter().filter(|binding| matches!(binding, Binding::Key(_))).copied().collect::<Vec<Binding>>();
+        assert!(Action::ALL.iter().all(|action| keys(&first, *action).iter().all(|key| !keys(&second, *action).contains(key))));
+        assert!(first.get(Action::Pause).is_empty());
+        let custom = Bindings::parse_over("hold = [\"C\"]", Bindings::versus(1)).unwrap();
+        assert_eq!(&[Binding::Key(KeyCode::C)], custom.get(Action::Hold));
+        assert_eq!(second.get(Action::MoveLeft), custom.get(Action::MoveLeft));
+    }
+}
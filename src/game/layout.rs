@@ -0,0 +1,134 @@
+use ggez::graphics::Rect;
+use game_core::Board;
+
+//Where everything in the game view goes, worked out from the board size and the screen it has to fit in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout
+{
+    pub origin: (f32, f32),
+    pub cell_size: f32,
+    pub cell_spacing: f32,
+    pub columns: u32,
+    pub rows: u32
+}
+impl Layout
+{
+    //Spacing as a part of the stride, 3 pixels of spacing for 20 pixel cells
+    const SPACING_RATIO: f32 = 3.0 / 23.0;
+    //Preview frames fit the largest piece box
+    const PREVIEW_CELLS: f32 = 4.0;
+    //Stacked next to the board, on short boards they are taller than it
+    pub const NEXT_PIECES: usize = 4;
+    //Free strides around the board, across and down, room for the labels and a border
+    const MARGIN: (f32, f32) = (2.0, 3.0);
+    //The size the menus and text were made for, everything else is scaled from it
+    const REFERENCE_SIZE: (f32, f32) = (800.0, 600.0);
+    const MENU_POSITION: (f32, f32) = (150.0, 100.0);
+
+    //Cells are as large as fits on screen, the whole view is centred
+    pub fn new(board: &Board, screen: Rect) -> Layout
+    {
+        let (columns, rows) = (board.width(), board.visible_height());
+        let across = columns as f32 + 2.0 * Layout::PREVIEW_CELLS + Layout::MARGIN.0;
+        let previews = Layout::NEXT_PIECES as f32 * (Layout::PREVIEW_CELLS + Layout::SPACING_RATIO);
+        let down = (rows as f32).max(previews) + Layout::MARGIN.1;
+        let stride = (screen.w / across).min(screen.h / down).max(0.0);
+        let cell_spacing = stride * Layout::SPACING_RATIO;
+        let mut layout = Layout { origin: (0.0, 0.0), cell_size: stride - cell_spacing, cell_spacing, columns, rows };
+        let size = (layout.board_size().0, layout.view_height());
+        layout.origin = (screen.x + (screen.w - size.0) / 2.0, screen.y + (screen.h - size.1) / 2.0);
+        layout
+    }
+    //How much larger the screen is than the one the menus were made for, keeping the aspect ratio
+    pub fn ui_scale(screen: Rect) -> f32
+    {
+        (screen.w / Layout::REFERENCE_SIZE.0).min(screen.h / Layout::REFERENCE_SIZE.1)
+    }
+    //Top left of a menu, on the same spot relative to the screen whatever its size
+    pub fn menu_position(screen: Rect) -> (f32, f32)
+    {
+        let scale = Layout::ui_scale(screen);
+        let offset = ((screen.w - Layout::REFERENCE_SIZE.0 * scale) / 2.0, (screen.h - Layout::REFERENCE_SIZE.1 * scale) / 2.0);
+        (screen.x + offset.0 + Layout::MENU_POSITION.0 * scale, screen.y + offset.1 + Layout::MENU_POSITION.1 * scale)
+    }
+    //Distance from one cell to the next
+    pub fn stride(&self) -> f32
+    {
+        self.cell_size + self.cell_spacing
+    }
+    pub fn label_height(&self) -> f32
+    {
+        self.cell_size
+    }
+    pub fn board_size(&self) -> (f32, f32)
+    {
+        (self.columns as f32 * self.stride() + self.cell_spacing, self.rows as f32 * self.stride() + self.cell_spacing)
+    }
+    //The board or the next column, whichever is taller
+    pub fn view_height(&self) -> f32
+    {
+        self.board_size().1.max(Layout::NEXT_PIECES as f32 * self.preview_size())
+    }
+    //Top left of the cell, in pixels
+    pub fn cell_position(&self, x: u32, y: u32) -> (f32, f32)
+    {
+        (self.origin.0 + self.cell_spacing + x as f32 * self.stride(), self.origin.1 + self.cell_spacing + y as f32 * self.stride())
+    }
+    //Side of the square hold and next frames
+    pub fn preview_size(&self) -> f32
+    {
+        Layout::PREVIEW_CELLS * self.stride() + self.cell_spacing
+    }
+    pub fn hold_position(&self) -> (f32, f32)
+    {
+        (self.origin.0 - self.preview_size(), self.origin.1)
+    }
+    pub fn next_position(&self, index: usize) -> (f32, f32)
+    {
+        (self.origin.0 + self.board_size().0, self.origin.1 + self.preview_size() * index as f32)
+    }
+    //Under the hold frame
+    pub fn stats_position(&self) -> (f32, f32)
+    {
+        let hold = self.hold_position();
+        (hold.0, hold.1 + self.preview_size() + self.cell_size)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn fits_and_centres_on_any_screen()
+    {
+        let board = Board::with_buffer(10, 20, 4);
+        for screen in [Rect::new(0.0, 0.0, 800.0, 600.0), Rect::new(0.0, 0.0, 1920.0, 400.0), Rect::new(0.0, 0.0, 300.0, 1000.0)].iter()
+        {
+            let layout = Layout::new(&board, *screen);
+            let size = layout.board_size();
+            let left = layout.hold_position().0;
+            let right = layout.next_position(0).0 + layout.preview_size();
+            assert!(left >= screen.x && right <= screen.x + screen.w);
+            assert!(layout.origin.1 - layout.label_height() >= screen.y && layout.origin.1 + size.1 <= screen.y + screen.h);
+            assert!((left - screen.x - (screen.x + screen.w - right)).abs() < 0.01);
+            assert!((layout.origin.1 - screen.y - (screen.y + screen.h - layout.origin.1 - size.1)).abs() < 0.01);
+        }
+        let small = Layout::new(&board, Rect::new(0.0, 0.0, 400.0, 300.0));
+        let large = Layout::new(&board, Rect::new(0.0, 0.0, 800.0, 600.0));
+        assert!((large.cell_size - small.cell_size * 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn short_boards_leave_room_for_the_previews()
+    {
+        let board = Board::with_buffer(10, 10, 4);
+        let screen = Rect::new(0.0, 0.0, 800.0, 600.0);
+        let layout = Layout::new(&board, screen);
+        let last = layout.next_position(Layout::NEXT_PIECES - 1);
+        assert!(layout.origin.1 - layout.label_height() >= screen.y);
+        assert!(last.1 + layout.preview_size() <= screen.y + screen.h);
+        assert!(layout.origin.1 + layout.board_size().1 <= last.1 + layout.preview_size());
+    }
+}
@@ -1,37 +1,144 @@
 use ggez::{Context, GameResult};
-use ggez::graphics::{self, DrawMode, Color};
-use crate::utility::Point;
+use ggez::graphics::{self, DrawMode, DrawParam, FilterMode, Image, MeshBuilder, Rect, Scale, Text, TextFragment};
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::nalgebra::{Point2, Vector2};
+use game_core::utility::{Point, Color};
+use super::layout::Layout;
+use super::theme::FrameStyle;
 
-pub struct Renderer
+//Counts for the last finished frame, shown in the debug overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats
 {
+    pub draw_calls: u32,
+    pub shapes: u32,
+    pub texts: u32
+}
 
+//Shapes are collected into one mesh, textured blocks into a sprite batch and text into ggez's text queue,
+//all are drawn on flush. Everything queued between two flushes costs at most three draw calls
+pub struct Renderer
+{
+    mesh: MeshBuilder,
+    blocks: Option<(SpriteBatch, (f32, f32))>,
+    shapes: u32,
+    sprites: u32,
+    texts: u32,
+    current: FrameStats,
+    last: FrameStats
 }
 impl Renderer
 {
-    //TODO: cache mesh? or rect?
-    //TODO: replace f32 tuple with struct for operator overloading
-    pub fn draw_tetromino(context: &mut Context, points: &[Point; 4], origin: (f32, f32), position: (f32, f32), cell_size: f32, cell_spacing: f32, color: Color) -> GameResult<()>
+    //ggez's own default, at the reference screen size
+    const FONT_SIZE: f32 = 16.0;
+
+    pub fn new() -> Renderer
+    {
+        Renderer { mesh: MeshBuilder::new(), blocks: None, shapes: 0, sprites: 0, texts: 0, current: FrameStats::default(), last: FrameStats::default() }
+    }
+    pub fn to_color(color: Color) -> graphics::Color
+    {
+        graphics::Color::new(color.r, color.g, color.b, color.a)
+    }
+    pub fn rectangle(&mut self, mode: DrawMode, rect: Rect, color: graphics::Color)
+    {
+        self.mesh.rectangle(mode, rect, color);
+        self.shapes += 1;
+    }
+    //Cells are drawn with the image tinted by their colour, or as plain squares without one
+    pub fn set_block_image(&mut self, image: Option<Image>)
+    {
+        self.blocks = image.map(|image|
+        {
+            let size = (image.width() as f32, image.height() as f32);
+            (SpriteBatch::new(image), size)
+        });
+        self.sprites = 0;
+    }
+    pub fn draw_cell(&mut self, position: (f32, f32), cell_size: f32, color: Color)
+    {
+        match &mut self.blocks
+        {
+            Some((batch, size)) =>
+            {
+                batch.add(DrawParam::new()
+                    .dest(Point2::new(position.0, position.1))
+                    .scale(Vector2::new(cell_size / size.0, cell_size / size.1))
+                    .color(Renderer::to_color(color)));
+                self.sprites += 1;
+            }
+            None => self.rectangle(DrawMode::fill(), Rect::new(position.0, position.1, cell_size, cell_size), Renderer::to_color(color))
+        }
+    }
+    //`position` is in cells from `origin` and may be fractional
+    pub fn draw_tetromino(&mut self, points: &[Point], origin: (f32, f32), position: (f32, f32), cell_size: f32, cell_spacing: f32, color: Color)
     {
+        let stride = cell_size + cell_spacing;
         for point in points.iter()
         {
-            let x_pos = origin.0 + cell_spacing + point.x as f32 * (cell_size + cell_spacing);
-            let y_pos = origin.1 + cell_spacing + point.y as f32 * (cell_size + cell_spacing);
-            let rect = graphics::Rect{ x: x_pos, y: y_pos, w: cell_size, h: cell_size};
-            let square = graphics::Mesh::new_rectangle(context, DrawMode::fill(), rect, color).unwrap();
-            graphics::draw(context, &square, (ggez::nalgebra::Point2::new(position.0, position.1) * (cell_size + cell_spacing),))?;
+            let x_pos = origin.0 + cell_spacing + (point.x as f32 + position.0) * stride;
+            let y_pos = origin.1 + cell_spacing + (point.y as f32 + position.1) * stride;
+            self.draw_cell((x_pos, y_pos), cell_size, color);
         }
-        Ok(())
     }
-    pub fn draw_frame(context: &mut Context, position: (f32, f32), size: (f32, f32)) -> GameResult<()>
+    pub fn draw_frame(&mut self, position: (f32, f32), size: (f32, f32), style: &FrameStyle)
     {
-        let hold_rect = graphics::Rect
+        self.rectangle(DrawMode::stroke(style.width), Rect::new(position.0, position.1, size.0, size.1), Renderer::to_color(style.color));
+    }
+    //Text grows and shrinks with the screen like the rest of the layout
+    pub fn draw_text(&mut self, context: &mut Context, text: &str, position: (f32, f32))
+    {
+        let scale = Scale::uniform(Renderer::FONT_SIZE * Layout::ui_scale(graphics::screen_coordinates(context)));
+        graphics::queue_text(context, &Text::new(TextFragment::new(text).scale(scale)), ggez::nalgebra::Point2::new(position.0, position.1), None);
+        self.texts += 1;
+    }
+    //Darkens everything drawn before it
+    pub fn draw_overlay(&mut self, context: &mut Context, alpha: f32)
+    {
+        self.rectangle(DrawMode::fill(), graphics::screen_coordinates(context), graphics::Color::new(0.0, 0.0, 0.0, alpha));
+    }
+    //Draws everything queued, shapes first so text stays readable on top
+    pub fn flush(&mut self, context: &mut Context) -> GameResult<()>
+    {
+        if self.shapes > 0
+        {
+            let mesh = self.mesh.build(context)?;
+            graphics::draw(context, &mesh, DrawParam::default())?;
+            self.mesh = MeshBuilder::new();
+            self.current.draw_calls += 1;
+            self.current.shapes += self.shapes;
+            self.shapes = 0;
+        }
+        if let Some((batch, _)) = &mut self.blocks
+        {
+            if self.sprites > 0
             {
-                x: position.0,
-                y: position.1,
-                w: size.0,
-                h: size.1
-            };
-            let hold_mesh = graphics::Mesh::new_rectangle(context, DrawMode::stroke(1.0), hold_rect, graphics::WHITE).unwrap();
-            graphics::draw(context, &hold_mesh, (ggez::nalgebra::Point2::new(0.0, 0.0),))
-    }
-}
\ No newline at end of file
+                graphics::draw(context, batch, DrawParam::default())?;
+                batch.clear();
+                self.current.draw_calls += 1;
+                self.current.shapes += self.sprites;
+                self.sprites = 0;
+            }
+        }
+        if self.texts > 0
+        {
+            graphics::draw_queued_text(context, DrawParam::default(), None, FilterMode::Linear)?;
+            self.current.draw_calls += 1;
+            self.current.texts += self.texts;
+            self.texts = 0;
+        }
+        Ok(())
+    }
+    //Flushes and keeps the frame's counts for `last_frame`
+    pub fn end_frame(&mut self, context: &mut Context) -> GameResult<()>
+    {
+        self.flush(context)?;
+        self.last = self.current;
+        self.current = FrameStats::default();
+        Ok(())
+    }
+    pub fn last_frame(&self) -> FrameStats
+    {
+        self.last
+    }
+}
@@ -0,0 +1,237 @@
+use std::{fmt, fs, io};
+use std::path::Path;
+use toml::Value;
+use game_core::TetrominoKind;
+use game_core::utility::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameStyle
+{
+    pub color: Color,
+    pub width: f32
+}
+
+//Everything about how the game looks, read from a theme file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme
+{
+    pub name: String,
+    //In `TetrominoKind` order
+    pub pieces: [Color; 7],
+    //Rows sent by an opponent
+    pub garbage: Color,
+    pub background: Color,
+    //Drawn in the spacing between cells, none leaves it empty
+    pub grid: Option<Color>,
+    pub ghost_alpha: f32,
+    pub frame: FrameStyle,
+    //Image tinted with the piece colour for every block, relative to the resources directory
+    pub block_sprite: Option<String>
+}
+
+#[derive(Debug)]
+pub enum ThemeError
+{
+    Io(io::Error),
+    Parse(String),
+    UnknownKey(String),
+    InvalidValue(String)
+}
+impl fmt::Display for ThemeError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            ThemeError::Io(e) => write!(f, "could not read theme: {}", e),
+            ThemeError::Parse(e) => write!(f, "could not parse theme: {}", e),
+            ThemeError::UnknownKey(name) => write!(f, "unknown theme key '{}'", name),
+            ThemeError::InvalidValue(name) => write!(f, "invalid value for '{}'", name)
+        }
+    }
+}
+impl std::error::Error for ThemeError {}
+impl From<io::Error> for ThemeError
+{
+    fn from(e: io::Error) -> ThemeError
+    {
+        ThemeError::Io(e)
+    }
+}
+
+impl Theme
+{
+    //Inside the resources directory, next to the block sprites the themes use
+    pub const DIRECTORY: &'static str = "themes";
+    const PIECE_NAMES: [&'static str; 7] = ["i", "j", "l", "o", "s", "t", "z"];
+
+    pub fn piece_color(&self, kind: TetrominoKind) -> Color
+    {
+        self.pieces[kind as usize]
+    }
+    //Keys missing from the file keep the default theme's values
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Theme, ThemeError>
+    {
+        Theme::parse(&fs::read_to_string(path)?)
+    }
+    pub fn parse(text: &str) -> Result<Theme, ThemeError>
+    {
+        let table = match text.parse::<Value>().map_err(|e| ThemeError::Parse(e.to_string()))?
+        {
+            Value::Table(table) => table,
+            _ => return Err(ThemeError::Parse("expected a table".to_string()))
+        };
+        let mut theme = Theme::default();
+        for (key, value) in table.iter()
+        {
+            match key.as_str()
+            {
+                "name" => theme.name = value.as_str().ok_or_else(|| ThemeError::InvalidValue(key.clone()))?.to_string(),
+                "background" => theme.background = parse_color(key, value)?,
+                "garbage" => theme.garbage = parse_color(key, value)?,
+                "grid" => theme.grid = Some(parse_color(key, value)?),
+                "ghost_alpha" => theme.ghost_alpha = parse_number(key, value)?,
+                "block_sprite" => theme.block_sprite = Some(value.as_str().ok_or_else(|| ThemeError::InvalidValue(key.clone()))?.to_string()),
+                "frame" =>
+                {
+                    for (name, value) in table_of(key, value)?.iter()
+                    {
+                        match name.as_str()
+                        {
+                            "color" => theme.frame.color = parse_color(name, value)?,
+                            "width" => theme.frame.width = parse_number(name, value)?,
+                            _ => return Err(ThemeError::UnknownKey(format!("frame.{}", name)))
+                        }
+                    }
+                }
+                "pieces" =>
+                {
+                    for (name, value) in table_of(key, value)?.iter()
+                    {
+                        let index = Theme::PIECE_NAMES.iter().position(|piece| piece == name).ok_or_else(|| ThemeError::UnknownKey(format!("pieces.{}", name)))?;
+                        theme.pieces[index] = parse_color(name, value)?;
+                    }
+                }
+                _ => return Err(ThemeError::UnknownKey(key.clone()))
+            }
+        }
+        Ok(theme)
+    }
+    //The default theme followed by every theme file in `directory`, files that fail to load are skipped
+    pub fn load_all<P: AsRef<Path>>(directory: P) -> Vec<Theme>
+    {
+        let mut themes = vec![Theme::default()];
+        let directory = directory.as_ref();
+        let mut paths: Vec<_> = match fs::read_dir(directory)
+        {
+            Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).filter(|path| path.extension().is_some_and(|ext| ext == "toml")).collect(),
+            Err(e) =>
+            {
+                println!("Only the {} theme is available, could not read {}: {}", themes[0].name, directory.display(), e);
+                return themes;
+            }
+        };
+        paths.sort();
+        for path in paths
+        {
+            match Theme::load(&path)
+            {
+                Ok(theme) => themes.push(theme),
+                Err(e) => println!("Skipping {}, {}", path.display(), e)
+            }
+        }
+        themes
+    }
+}
+impl Default for Theme
+{
+    fn default() -> Theme
+    {
+        Theme
+        {
+            name: "Classic".to_string(),
+            pieces:
+            [
+                Color::new(0.27, 0.96, 0.95, 1.0),
+                Color::new(0.18, 0.0, 0.84, 1.0),
+                Color::new(0.91, 0.65, 0.05, 1.0),
+                Color::new(0.92, 0.96, 0.06, 1.0),
+                Color::new(0.18, 0.96, 0.0, 1.0),
+                Color::new(0.63, 0.0, 0.94, 1.0),
+                Color::new(0.96, 0.05, 0.07, 1.0)
+            ],
+            garbage: Color::new(0.5, 0.5, 0.5, 1.0),
+            background: Color::new(0.0, 0.0, 0.0, 1.0),
+            grid: None,
+            ghost_alpha: 0.2,
+            frame: FrameStyle { color: Color::new(1.0, 1.0, 1.0, 1.0), width: 1.0 },
+            block_sprite: None
+        }
+    }
+}
+
+fn table_of<'a>(key: &str, value: &'a Value) -> Result<&'a toml::value::Table, ThemeError>
+{
+    value.as_table().ok_or_else(|| ThemeError::InvalidValue(key.to_string()))
+}
+fn parse_number(key: &str, value: &Value) -> Result<f32, ThemeError>
+{
+    value.as_float().or_else(|| value.as_integer().map(|n| n as f64)).map(|n| n as f32).ok_or_else(|| ThemeError::InvalidValue(key.to_string()))
+}
+//"#rrggbb" or "#rrggbbaa"
+fn parse_color(key: &str, value: &Value) -> Result<Color, ThemeError>
+{
+    let invalid = || ThemeError::InvalidValue(key.to_string());
+    let hex = value.as_str().and_then(|text| text.strip_prefix('#')).ok_or_else(invalid)?;
+    if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii()
+    {
+        return Err(invalid());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map(|c| c as f32 / 255.0).map_err(|_| invalid());
+    let alpha = if hex.len() == 8 { channel(3)? } else { 1.0 };
+    Ok(Color::new(channel(0)?, channel(1)?, channel(2)?, alpha))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn parses_themes_over_the_default()
+    {
+        let theme = Theme::parse("name = \"Test\"\nghost_alpha = 0.5\ngarbage = \"#404040\"\ngrid = \"#ff000080\"\n[pieces]\nt = \"#00ff00\"\n[frame]\nwidth = 2").unwrap();
+        assert_eq!("Test", theme.name);
+        assert_eq!(0.5, theme.ghost_alpha);
+        assert_eq!(Color::new(64.0 / 255.0, 64.0 / 255.0, 64.0 / 255.0, 1.0), theme.garbage);
+        assert_eq!(Some(Color::new(1.0, 0.0, 0.0, 128.0 / 255.0)), theme.grid);
+        assert_eq!(Color::new(0.0, 1.0, 0.0, 1.0), theme.piece_color(TetrominoKind::T));
+        assert_eq!(Theme::default().piece_color(TetrominoKind::I), theme.piece_color(TetrominoKind::I));
+        assert_eq!(2.0, theme.frame.width);
+        assert_eq!(Theme::default().frame.color, theme.frame.color);
+    }
+
+    #[test]
+    fn invalid_themes_are_errors()
+    {
+        assert!(matches!(Theme::parse("colour = \"#ffffff\""), Err(ThemeError::UnknownKey(_))));
+        assert!(matches!(Theme::parse("[pieces]\nx = \"#ffffff\""), Err(ThemeError::UnknownKey(_))));
+        assert!(matches!(Theme::parse("background = \"white\""), Err(ThemeError::InvalidValue(_))));
+        assert!(matches!(Theme::parse("background = \"#fff\""), Err(ThemeError::InvalidValue(_))));
+        assert!(matches!(Theme::parse("ghost_alpha = \"high\""), Err(ThemeError::InvalidValue(_))));
+        assert!(matches!(Theme::parse("name = "), Err(ThemeError::Parse(_))));
+    }
+
+    #[test]
+    fn shipped_themes_load_with_their_sprites()
+    {
+        let resources = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources");
+        let themes = Theme::load_all(resources.join(Theme::DIRECTORY));
+        let names: Vec<&str> = themes.iter().map(|theme| theme.name.as_str()).collect();
+        assert_eq!(vec!["Classic", "Blocks", "Monochrome", "Neon"], names);
+        let sprites: Vec<&String> = themes.iter().filter_map(|theme| theme.block_sprite.as_ref()).collect();
+        assert!(!sprites.is_empty());
+        //Sprite paths are ggez resource paths, rooted at the resources directory
+        assert!(sprites.iter().all(|sprite| resources.join(sprite.trim_start_matches('/')).is_file()));
+    }
+}
@@ -0,0 +1,154 @@
+use ggez::{Context, GameResult, graphics, timer};
+use ggez::graphics::Rect;
+use game_core::{GameState, Lockstep};
+use crate::scene::{Scene, Shared, Settings, Transition, Command};
+use crate::scene::pause_menu::PauseMenu;
+use crate::scene::versus_over::VersusOverMenu;
+use crate::scene::main_menu::MainMenu;
+use super::Game;
+use super::renderer::Renderer;
+use super::layout::Layout;
+use super::input::bindings::Action;
+
+//Two players side by side, each with their own bindings. Lines one sends rise on the other's board.
+//Online the other board belongs to a peer and both are stepped in lockstep with it
+pub struct Versus
+{
+    players: [Game; 2],
+    session: Option<Lockstep>
+}
+impl Versus
+{
+    pub fn new(settings: &Settings) -> Versus
+    {
+        let seed = GameState::random_seed();
+        Versus { players: [Game::versus(settings, seed), Game::versus(settings, seed)], session: None }
+    }
+    //Seed and rules come from the host
+    pub fn online(settings: &Settings, session: Lockstep) -> Versus
+    {
+        let start = *session.start();
+        let settings = Settings { rules: start.rules, garbage: start.garbage, ..*settings };
+        Versus { players: [Game::versus(&settings, start.seed), Game::versus(&settings, start.seed)], session: Some(session) }
+    }
+    fn update_online(&mut self, context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        //The peer can't wait on a pause menu, so pausing leaves the game
+        if shared.input.get_action_down(Action::Pause)
+        {
+            return Ok(Transition::Reset(Box::new(MainMenu::new())));
+        }
+        if self.is_over()
+        {
+            return Ok(Transition::None);
+        }
+        let session = match &mut self.session
+        {
+            Some(session) => session,
+            None => return Ok(Transition::None)
+        };
+
+        let inputs = Game::player_inputs(&shared.input);
+        let [first, second] = &mut self.players;
+        if let Err(e) = session.step(timer::delta(context).as_secs_f32(), &inputs, [&mut first.state, &mut second.state])
+        {
+            self.session = None;
+            return Ok(Transition::Push(Box::new(VersusOverMenu::connection_lost(&e.to_string()))));
+        }
+        let quit = session.has_ended();
+        if self.is_over()
+        {
+            return Ok(Transition::Push(Box::new(VersusOverMenu::new([&self.players[0].state, &self.players[1].state], false))));
+        }
+        if quit
+        {
+            self.session = None;
+            return Ok(Transition::Push(Box::new(VersusOverMenu::connection_lost("The other player left"))));
+        }
+        Ok(Transition::None)
+    }
+    fn restart(&mut self, settings: &Settings)
+    {
+        *self = Versus::new(settings);
+    }
+    fn is_over(&self) -> bool
+    {
+        self.players.iter().any(|player| player.state.is_game_over())
+    }
+    //Garbage either side sent this update goes to the other
+    fn exchange_garbage(&mut self)
+    {
+        let sent = [self.players[0].state.take_outgoing(), self.players[1].state.take_outgoing()];
+        self.players[0].state.receive_garbage(sent[1]);
+        self.players[1].state.receive_garbage(sent[0]);
+    }
+}
+impl Scene for Versus
+{
+    fn update(&mut self, context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        if self.session.is_some()
+        {
+            return self.update_online(context, shared);
+        }
+        if shared.input.get_action_down(Action::Pause)
+        {
+            return Ok(Transition::Push(Box::new(PauseMenu::new())));
+        }
+        if shared.input.get_action_down(Action::Restart)
+        {
+            self.restart(&shared.settings);
+        }
+        if self.is_over()
+        {
+            return Ok(Transition::None);
+        }
+
+        let delta_time = timer::delta(context).as_secs_f32();
+        for (player, input) in self.players.iter_mut().zip(shared.players.iter())
+        {
+            player.step_player(delta_time, input);
+        }
+        self.exchange_garbage();
+        if !self.is_over()
+        {
+            return Ok(Transition::None);
+        }
+        Ok(Transition::Push(Box::new(VersusOverMenu::new([&self.players[0].state, &self.players[1].state], true))))
+    }
+    fn on_return(&mut self, shared: &mut Shared, command: Option<Command>)
+    {
+        if command == Some(Command::Restart)
+        {
+            self.restart(&shared.settings);
+        }
+    }
+    fn on_exit(&mut self)
+    {
+        if let Some(session) = &mut self.session
+        {
+            //Leaving anyway, a peer that is already gone doesn't matter
+            let _ = session.send_quit();
+        }
+    }
+    fn draw(&mut self, context: &mut Context, shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        let screen = graphics::screen_coordinates(context);
+        let half = screen.w / 2.0;
+        for (i, player) in self.players.iter().enumerate()
+        {
+            let area = Rect::new(screen.x + half * i as f32, screen.y, half, screen.h);
+            player.draw_in(context, shared, renderer, area);
+            //Over the board, between the hold and next labels
+            let layout = Layout::new(player.state.board(), area);
+            let label = match &self.session
+            {
+                Some(session) if session.local_player() == i => "YOU".to_string(),
+                Some(_) => "OPPONENT".to_string(),
+                None => format!("PLAYER {}", i + 1)
+            };
+            renderer.draw_text(context, &label, (layout.origin.0, layout.origin.1 - layout.label_height()));
+        }
+        Ok(())
+    }
+}
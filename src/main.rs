@@ -1,31 +1,240 @@
+use std::{env, path, thread};
+use std::net::TcpListener;
 use ggez::ContextBuilder;
 use ggez::event;
+use game_core::{Replay, GameState, GameMode, Bot, BotPlayer, Lockstep, NetError};
+use game_core::net::Start;
 
 mod game;
-mod utility;
+mod scene;
 
-///TODO
-/// Kicking
-/// Score
-/// Holding 
-/// Redo clearing, use naive instead of cascade
-/// 
-/// BUGS
-/// next pieces sometimes wrong
+//Themes and block sprites live in one resources directory, next to Cargo.toml when run through cargo
+//and otherwise next to the executable, where ggez looks by default
+fn resources_dir() -> path::PathBuf
+{
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR")
+    {
+        return path::Path::new(&manifest_dir).join("resources");
+    }
+    let executable_dir = env::current_exe().ok().and_then(|exe| exe.parent().map(path::Path::to_path_buf));
+    executable_dir.unwrap_or_default().join("resources")
+}
+
+//The value after `flag`, None without the flag and an error naming `what` when it has no value
+fn flag_value<'a>(args: &'a [String], flag: &str, what: &str) -> Result<Option<&'a str>, String>
+{
+    match args.iter().position(|arg| arg == flag)
+    {
+        None => Ok(None),
+        Some(i) => match args.get(i + 1).filter(|value| !value.starts_with("--"))
+        {
+            Some(value) => Ok(Some(value)),
+            None => Err(format!("{} needs {}", flag, what))
+        }
+    }
+}
+
+
+//The bot plays the local board of an online game until either side loses or leaves
+fn play_online(mut session: Lockstep) -> Result<(), NetError>
+{
+    let mut states = session.create_states();
+    let mut bot = BotPlayer::new(Bot::default());
+    let local = session.local_player();
+    while !states.iter().any(GameState::is_game_over) && !session.has_ended()
+    {
+        if session.can_send()
+        {
+            let inputs = bot.next_inputs(&states[local]);
+            session.send_inputs(inputs)?;
+        }
+        let [first, second] = &mut states;
+        if session.advance([first, second])? == 0
+        {
+            thread::yield_now();
+        }
+    }
+    session.send_quit()?;
+    println!("Seed: {}\nFrame: {}", session.start().seed, states[0].frame());
+    for (i, state) in states.iter().enumerate()
+    {
+        println!("Player {}{}: {} pieces, {} lines sent{}", i + 1, if i == local { " (bot)" } else { "" }, state.pieces_placed(), state.lines_sent(), if state.is_game_over() { ", game over" } else { "" });
+    }
+    println!("Checksum: {:016x}", Lockstep::checksum(&states[0], &states[1]));
+    Ok(())
+}
 
 fn main() {
     
+    //--replay <file> plays a recorded game instead of reading input
+    let args: Vec<String> = env::args().collect();
+    let replay = match flag_value(&args, "--replay", "a file").and_then(|path| path.map(|path| Replay::load(path).map_err(|e| e.to_string())).transpose())
+    {
+        Ok(replay) => replay,
+        Err(e) =>
+        {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+
+    //--mode <name> starts a game in that mode right away, bot games default to endless and versus starts a two player game
+    let mode = match flag_value(&args, "--mode", "a mode").map(|name| name.map(GameMode::from_name))
+    {
+        Ok(None) => None,
+        Ok(Some(Some(mode))) => Some(mode),
+        _ =>
+        {
+            println!("Error: --mode needs one of endless, sprint, ultra, marathon, zen or versus");
+            return;
+        }
+    };
+
+    //--host [port] waits for another player, --join <address> connects to one. Both play versus over the network.
+    //With a window the host waits in a lobby that can be cancelled, headless hosting and joining block until connected
+    let host_port = match args.iter().position(|arg| arg == "--host").map(|i| args.get(i + 1).filter(|port| !port.starts_with("--")).map(|port| port.parse::<u16>()))
+    {
+        None => None,
+        Some(None) => Some(Lockstep::DEFAULT_PORT),
+        Some(Some(Ok(port))) => Some(port),
+        Some(Some(Err(_))) =>
+        {
+            println!("Error: --host needs a port number");
+            return;
+        }
+    };
+    let join_address = match flag_value(&args, "--join", "an address")
+    {
+        Ok(address) => address,
+        Err(e) =>
+        {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    //--scores <file> keeps the high scores somewhere other than the user data directory
+    let scores_path = match flag_value(&args, "--scores", "a file")
+    {
+        Ok(path) => path.map(path::PathBuf::from),
+        Err(e) =>
+        {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+    let listener = match host_port.map(|port| TcpListener::bind(("0.0.0.0", port)))
+    {
+        None => None,
+        Some(Ok(listener)) => Some(listener),
+        Some(Err(e)) =>
+        {
+            println!("Error: could not listen for players, {}", e);
+            return;
+        }
+    };
+    let session = match join_address.map(|address|
+    {
+        println!("Connecting to {}", address);
+        Lockstep::join(address)
+    })
+    {
+        None => None,
+        Some(Ok(session)) => Some(session),
+        Some(Err(e)) =>
+        {
+            println!("Error: {}", e);
+            return;
+        }
+    };
+
+    //--bot lets the bot play, with --headless it plays without a window and prints the result
+    let use_bot = args.iter().any(|arg| arg == "--bot");
+    if use_bot && args.iter().any(|arg| arg == "--headless")
+    {
+        //Headless games have no settings screen, they use the defaults
+        let session = match (session, &listener)
+        {
+            (Some(session), _) => Some(Ok(session)),
+            (None, Some(listener)) =>
+            {
+                println!("Waiting for a player on port {}", host_port.unwrap_or(Lockstep::DEFAULT_PORT));
+                let settings = scene::Settings::default();
+                Some(Lockstep::host(listener, Start { seed: GameState::random_seed(), rules: settings.rules, garbage: settings.garbage }))
+            }
+            (None, None) => None
+        };
+        if let Some(session) = session
+        {
+            if let Err(e) = session.and_then(play_online)
+            {
+                println!("Error: {}", e);
+            }
+            return;
+        }
+        let max_pieces = match flag_value(&args, "--pieces", "a number").map(|n| n.map(str::parse::<u32>))
+        {
+            Ok(None) => 1000,
+            Ok(Some(Ok(n))) => n,
+            _ =>
+            {
+                println!("Error: --pieces needs a number");
+                return;
+            }
+        };
+        let mut state = GameState::new();
+        state.set_mode(mode.unwrap_or_default());
+        BotPlayer::new(Bot::default()).play(&mut state, max_pieces);
+        let score = state.score();
+        println!("Seed: {}\nPieces: {}\nLines: {}\nScore: {}\nTime: {:.1}s{}", state.seed(), state.pieces_placed(), score.lines(), score.score(), state.elapsed(), match state.game_over() { Some(reason) if reason.is_top_out() => "\nGame over", Some(_) => "\nCompleted", None => "" });
+        return;
+    }
+
     let window_setup = ggez::conf::WindowSetup::default().title("Tetris");
     let window_mode = ggez::conf::WindowMode::default().resizable(true);
+    let resources = resources_dir();
     let (mut context, mut event_loop) = ContextBuilder::new("game", "molvin")
     .window_setup(window_setup)
     .window_mode(window_mode)
+    .add_resource_path(&resources)
     .build()
     .expect("failed to create ggez context");
+    let data_directory = ggez::filesystem::user_data_dir(&context).to_path_buf();
+    let scores_path = scores_path.unwrap_or_else(|| data_directory.join(game::high_scores::ScoreBoard::FILE_NAME));
+
+    //Online games, replays, bot games and games with a mode given start right away, otherwise start at the main menu
+    let shared = scene::Shared::new(&scores_path, data_directory.join(game::Game::REPLAY_DIRECTORY), &resources);
+    let first: Box<dyn scene::Scene> = if let Some(session) = session
+    {
+        Box::new(game::versus::Versus::online(&shared.settings, session))
+    }
+    else if let Some(listener) = listener
+    {
+        match scene::lobby::HostLobby::new(listener)
+        {
+            Ok(lobby) => Box::new(lobby),
+            Err(e) =>
+            {
+                println!("Error: could not listen for players, {}", e);
+                return;
+            }
+        }
+    }
+    else if mode == Some(GameMode::Versus) && replay.is_none() && !use_bot
+    {
+        Box::new(game::versus::Versus::new(&shared.settings))
+    }
+    else if replay.is_some() || use_bot || mode.is_some()
+    {
+        let bot = if use_bot { Some(BotPlayer::new(Bot::default())) } else { None };
+        Box::new(game::Game::new(&shared, mode.unwrap_or_default(), replay, bot))
+    }
+    else
+    {
+        Box::new(scene::main_menu::MainMenu::new())
+    };
+    let mut scenes = scene::SceneStack::new(shared, first);
     
-    let mut game = game::Game::new(&mut context);
-    
-    match event::run(&mut context, &mut event_loop, &mut game)
+    match event::run(&mut context, &mut event_loop, &mut scenes)
     {
         Ok(_) => println!("Exited cleanly"),
         Err(e) => println!("Error: {}", e)
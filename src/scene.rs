@@ -0,0 +1,287 @@
+use std::path::{Path, PathBuf};
+use ggez::{Context, GameResult, graphics, timer};
+use ggez::event::{self, EventHandler, Button, Axis, GamepadId};
+use game_core::{Rules, GarbageRules};
+use crate::game::input::Input;
+use crate::game::input::bindings::{Action, Bindings, InputError};
+use crate::game::renderer::Renderer;
+use crate::game::theme::Theme;
+use crate::game::high_scores::ScoreBoard;
+
+pub mod menu;
+pub mod main_menu;
+pub mod mode_select;
+pub mod pause_menu;
+pub mod game_over;
+pub mod settings;
+pub mod high_scores;
+pub mod versus_over;
+pub mod lobby;
+
+//Sent down to the scene below when the top one is popped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command
+{
+    Restart
+}
+
+//What a scene wants the stack to do after its update
+pub enum Transition
+{
+    None,
+    Push(Box<dyn Scene>),
+    Pop(Option<Command>),
+    //Clears the stack and starts over from this scene
+    Reset(Box<dyn Scene>),
+    Quit
+}
+
+pub trait Scene
+{
+    fn update(&mut self, context: &mut Context, shared: &mut Shared) -> GameResult<Transition>;
+    fn draw(&mut self, context: &mut Context, shared: &Shared, renderer: &mut Renderer) -> GameResult<()>;
+    //Overlays are drawn on top of the scene below instead of replacing it
+    fn is_overlay(&self) -> bool
+    {
+        false
+    }
+    //The scene above was popped and this one is on top again
+    fn on_return(&mut self, _shared: &mut Shared, _command: Option<Command>) {}
+    //The scene is about to be dropped, either popped, reset or because the game quits
+    fn on_exit(&mut self) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings
+{
+    //Used from the next game on, changing rules mid game would break its replay
+    pub rules: Rules,
+    pub ghost_piece: bool,
+    //Index into `Shared::themes`
+    pub theme: usize,
+    //How versus games send garbage
+    pub garbage: GarbageRules
+}
+impl Default for Settings
+{
+    fn default() -> Settings
+    {
+        Settings { rules: Rules::default(), ghost_piece: true, theme: 0, garbage: GarbageRules::default() }
+    }
+}
+
+//State every scene can reach
+pub struct Shared
+{
+    pub input: Input,
+    //One per player in versus games, pausing and menus still go through `input`
+    pub players: [Input; 2],
+    pub settings: Settings,
+    pub high_scores: ScoreBoard,
+    //Finished games are saved here as replays
+    pub replay_directory: PathBuf,
+    pub themes: Vec<Theme>
+}
+impl Shared
+{
+    const BINDINGS_PATH: &'static str = "bindings.toml";
+    const PLAYER_BINDINGS_PATHS: [&'static str; 2] = ["bindings_p1.toml", "bindings_p2.toml"];
+
+    //Themes are read from `resources`, the same directory ggez loads their block sprites from
+    pub fn new(scores_path: &Path, replay_directory: PathBuf, resources: &Path) -> Shared
+    {
+        let bindings = match Bindings::load(Shared::BINDINGS_PATH)
+        {
+            Ok(bindings) => bindings,
+            Err(e) =>
+            {
+                println!("Using default bindings, {}", e);
+                Bindings::default()
+            }
+        };
+        let player = |index: usize| match Bindings::load_over(Shared::PLAYER_BINDINGS_PATHS[index], Bindings::versus(index))
+        {
+            Ok(bindings) => Input::new(bindings),
+            //No file just means the player keeps the defaults
+            Err(InputError::Io(_)) => Input::new(Bindings::versus(index)),
+            Err(e) =>
+            {
+                println!("Using default bindings for player {}, {}", index + 1, e);
+                Input::new(Bindings::versus(index))
+            }
+        };
+        Shared { input: Input::new(bindings), players: [player(0), player(1)], settings: Settings::default(), high_scores: ScoreBoard::load(scores_path), replay_directory, themes: Theme::load_all(resources.join(Theme::DIRECTORY)) }
+    }
+    pub fn theme(&self) -> &Theme
+    {
+        &self.themes[self.settings.theme]
+    }
+}
+
+//Root event handler, only the top scene updates but overlays let the scenes below show through
+pub struct SceneStack
+{
+    shared: Shared,
+    scenes: Vec<Box<dyn Scene>>,
+    renderer: Renderer,
+    //The theme the renderer's block image was loaded for
+    applied_theme: Option<usize>,
+    //In the order they were first used, the first two belong to the versus players
+    gamepads: Vec<GamepadId>,
+    show_debug: bool
+}
+impl SceneStack
+{
+    pub fn new(shared: Shared, scene: Box<dyn Scene>) -> SceneStack
+    {
+        SceneStack { shared, scenes: vec![scene], renderer: Renderer::new(), applied_theme: None, gamepads: Vec::new(), show_debug: false }
+    }
+    fn exit_all(&mut self)
+    {
+        for scene in self.scenes.iter_mut().rev()
+        {
+            scene.on_exit();
+        }
+        self.scenes.clear();
+    }
+    fn player_input(&mut self, id: GamepadId) -> Option<&mut Input>
+    {
+        let index = match self.gamepads.iter().position(|gamepad| *gamepad == id)
+        {
+            Some(index) => index,
+            None =>
+            {
+                self.gamepads.push(id);
+                self.gamepads.len() - 1
+            }
+        };
+        self.shared.players.get_mut(index)
+    }
+    fn apply_theme(&mut self, context: &mut Context)
+    {
+        if self.applied_theme == Some(self.shared.settings.theme)
+        {
+            return;
+        }
+        self.applied_theme = Some(self.shared.settings.theme);
+        let image = self.shared.theme().block_sprite.as_ref().and_then(|path| match graphics::Image::new(context, path)
+        {
+            Ok(image) => Some(image),
+            Err(e) =>
+            {
+                println!("Drawing plain blocks, could not load {}: {}", path, e);
+                None
+            }
+        });
+        self.renderer.set_block_image(image);
+    }
+    fn apply(&mut self, context: &mut Context, transition: Transition)
+    {
+        match transition
+        {
+            Transition::None => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop(command) =>
+            {
+                if let Some(mut scene) = self.scenes.pop()
+                {
+                    scene.on_exit();
+                }
+                match self.scenes.last_mut()
+                {
+                    Some(scene) => scene.on_return(&mut self.shared, command),
+                    None => event::quit(context)
+                }
+            }
+            Transition::Reset(scene) =>
+            {
+                self.exit_all();
+                self.scenes.push(scene);
+            }
+            Transition::Quit =>
+            {
+                self.exit_all();
+                event::quit(context);
+            }
+        }
+    }
+}
+impl EventHandler for SceneStack
+{
+    fn update(&mut self, context: &mut Context) -> GameResult<()>
+    {
+        self.shared.input.update(context);
+        for player in self.shared.players.iter_mut()
+        {
+            player.update(context);
+        }
+        if self.shared.input.get_action_down(Action::DebugOverlay)
+        {
+            self.show_debug = !self.show_debug;
+        }
+        let transition = match self.scenes.last_mut()
+        {
+            Some(scene) => scene.update(context, &mut self.shared)?,
+            None => Transition::Quit
+        };
+        self.apply(context, transition);
+        Ok(())
+    }
+    fn draw(&mut self, context: &mut Context) -> GameResult<()>
+    {
+        self.apply_theme(context);
+        graphics::clear(context, Renderer::to_color(self.shared.theme().background));
+        //Start from the topmost scene that covers the whole screen
+        let first = self.scenes.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+        for scene in self.scenes[first..].iter_mut()
+        {
+            scene.draw(context, &self.shared, &mut self.renderer)?;
+            self.renderer.flush(context)?;
+        }
+        if self.show_debug
+        {
+            let stats = self.renderer.last_frame();
+            let text = format!("FPS: {:.0}\nDraw calls: {}\nShapes: {}\nTexts: {}", timer::fps(context), stats.draw_calls, stats.shapes, stats.texts);
+            self.renderer.draw_text(context, &text, (5.0, 5.0));
+        }
+        self.renderer.end_frame(context)?;
+        graphics::present(context)
+    }
+    //Screen coordinates follow the window so nothing is stretched, the layout refits itself next draw
+    fn resize_event(&mut self, context: &mut Context, width: f32, height: f32)
+    {
+        if let Err(e) = graphics::set_screen_coordinates(context, graphics::Rect::new(0.0, 0.0, width, height))
+        {
+            println!("Error: {}", e);
+        }
+    }
+    fn quit_event(&mut self, _context: &mut Context) -> bool
+    {
+        self.exit_all();
+        false
+    }
+    fn gamepad_button_down_event(&mut self, _context: &mut Context, button: Button, id: GamepadId)
+    {
+        self.shared.input.on_button(button, true);
+        if let Some(player) = self.player_input(id)
+        {
+            player.on_button(button, true);
+        }
+    }
+    fn gamepad_button_up_event(&mut self, _context: &mut Context, button: Button, id: GamepadId)
+    {
+        self.shared.input.on_button(button, false);
+        if let Some(player) = self.player_input(id)
+        {
+            player.on_button(button, false);
+        }
+    }
+    fn gamepad_axis_event(&mut self, _context: &mut Context, axis: Axis, value: f32, id: GamepadId)
+    {
+        self.shared.input.on_axis(axis, value);
+        if let Some(player) = self.player_input(id)
+        {
+            player.on_axis(axis, value);
+        }
+    }
+}
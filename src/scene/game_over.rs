@@ -0,0 +1,83 @@
+use ggez::{Context, GameResult};
+use game_core::{GameState, GameOver};
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use crate::game::hud::Hud;
+use crate::game::high_scores::ScoreBoard;
+use super::{Scene, Shared, Transition, Command};
+use super::menu::Menu;
+use super::main_menu::MainMenu;
+use super::high_scores::format_entry;
+
+pub struct GameOverMenu
+{
+    menu: Menu
+}
+impl GameOverMenu
+{
+    const RESTART: usize = 0;
+    const MAIN_MENU: usize = 1;
+    const TABLE_ROWS: usize = 5;
+
+    //`rank` is the place the game got on the mode's high score table, if any
+    pub fn new(state: &GameState, scores: &ScoreBoard, rank: Option<usize>) -> GameOverMenu
+    {
+        let (heading, reason) = match state.game_over()
+        {
+            Some(GameOver::BlockOut) => ("GAME OVER", "Block out"),
+            Some(GameOver::LockOut) => ("GAME OVER", "Lock out"),
+            Some(GameOver::TopOut) => ("GAME OVER", "Top out"),
+            Some(GameOver::Completed) => ("COMPLETE", if state.mode().time_limit().is_some() { "Time up" } else { "Goal reached" }),
+            None => ("", "")
+        };
+        let score = state.score();
+        let mut title = format!
+        (
+            "{}\n{} {}\n\nScore: {}\nLevel: {}\nLines: {}\nPieces: {}\nTime: {}",
+            heading, state.mode().name(), reason, score.score(), score.level(), score.lines(), state.pieces_placed(), Hud::format_time(state.elapsed())
+        );
+        if let Some(rank) = rank
+        {
+            title.push_str(&format!("\nNew high score, #{}", rank + 1));
+        }
+        //The top of the table, with the new entry marked even when it placed below the rows shown
+        let mode = state.mode();
+        let table = scores.table(mode);
+        if !table.is_empty()
+        {
+            title.push_str("\n\nHIGH SCORES");
+            for (i, entry) in table.iter().enumerate().filter(|(i, _)| *i < GameOverMenu::TABLE_ROWS || Some(*i) == rank)
+            {
+                let marker = if Some(i) == rank { ">" } else { " " };
+                title.push_str(&format!("\n{}{}", marker, format_entry(mode, i, entry)));
+            }
+        }
+        GameOverMenu { menu: Menu::new(&title, &["Restart", "Main menu"]) }
+    }
+}
+impl Scene for GameOverMenu
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        if shared.input.get_action_down(Action::Restart)
+        {
+            return Ok(Transition::Pop(Some(Command::Restart)));
+        }
+        Ok(match self.menu.update(&shared.input)
+        {
+            Some(GameOverMenu::RESTART) => Transition::Pop(Some(Command::Restart)),
+            Some(GameOverMenu::MAIN_MENU) => Transition::Reset(Box::new(MainMenu::new())),
+            _ => Transition::None
+        })
+    }
+    fn draw(&mut self, context: &mut Context, _shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        renderer.draw_overlay(context, 0.8);
+        self.menu.draw(context, renderer);
+        Ok(())
+    }
+    fn is_overlay(&self) -> bool
+    {
+        true
+    }
+}
@@ -0,0 +1,63 @@
+use ggez::{Context, GameResult, graphics};
+use game_core::GameMode;
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use crate::game::layout::Layout;
+use crate::game::hud::Hud;
+use crate::game::high_scores::{HighScore, ScoreBoard};
+use super::{Scene, Shared, Transition};
+
+//One line of a mode's table, Sprint shows the time it was cleared in instead of the score
+pub fn format_entry(mode: GameMode, rank: usize, entry: &HighScore) -> String
+{
+    let result = if ScoreBoard::ranks_by_time(mode) { Hud::format_time(entry.seconds()) } else { entry.score.to_string() };
+    format!("{:>2}. {:>8}  {:>3} lines  {}  seed {}", rank + 1, result, entry.lines, entry.format_date(), entry.seed)
+}
+
+//A table per mode, left and right switch between them
+pub struct HighScores
+{
+    mode: usize
+}
+impl HighScores
+{
+    pub fn new() -> HighScores
+    {
+        HighScores { mode: 0 }
+    }
+}
+impl Scene for HighScores
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        let input = &shared.input;
+        let count = GameMode::RANKED.len();
+        if input.get_action_down(Action::MoveLeft)
+        {
+            self.mode = (self.mode + count - 1) % count;
+        }
+        if input.get_action_down(Action::MoveRight)
+        {
+            self.mode = (self.mode + 1) % count;
+        }
+        Ok(if input.get_action_down(Action::Back) || input.get_action_down(Action::Confirm) { Transition::Pop(None) } else { Transition::None })
+    }
+    fn draw(&mut self, context: &mut Context, shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        let mode = GameMode::RANKED[self.mode];
+        let mut text = format!("HIGH SCORES\n< {} >\n", mode.name().to_uppercase());
+        let table = shared.high_scores.table(mode);
+        for (i, entry) in table.iter().enumerate()
+        {
+            text.push('\n');
+            text.push_str(&format_entry(mode, i, entry));
+        }
+        if table.is_empty()
+        {
+            text.push_str("\nNo games played yet");
+        }
+        text.push_str("\n\nLeft and right change mode, press back to return");
+        renderer.draw_text(context, &text, Layout::menu_position(graphics::screen_coordinates(context)));
+        Ok(())
+    }
+}
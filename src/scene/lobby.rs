@@ -0,0 +1,70 @@
+use std::io;
+use std::net::TcpListener;
+use ggez::{Context, GameResult, graphics};
+use game_core::{GameState, Lockstep};
+use game_core::net::Start;
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use crate::game::layout::Layout;
+use crate::game::versus::Versus;
+use super::{Scene, Shared, Transition};
+use super::main_menu::MainMenu;
+
+//Waits for a player to join an online game without blocking the window, back cancels
+pub struct HostLobby
+{
+    listener: TcpListener,
+    port: u16,
+    //The last player that failed to connect
+    error: Option<String>
+}
+impl HostLobby
+{
+    pub fn new(listener: TcpListener) -> io::Result<HostLobby>
+    {
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        Ok(HostLobby { listener, port, error: None })
+    }
+}
+impl Scene for HostLobby
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        if shared.input.get_action_down(Action::Back) || shared.input.get_action_down(Action::Pause)
+        {
+            return Ok(Transition::Reset(Box::new(MainMenu::new())));
+        }
+        let stream = match self.listener.accept()
+        {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(Transition::None),
+            Err(e) =>
+            {
+                self.error = Some(e.to_string());
+                return Ok(Transition::None);
+            }
+        };
+        //The game is played with this player's settings
+        let settings = &shared.settings;
+        match Lockstep::accept(stream, Start { seed: GameState::random_seed(), rules: settings.rules, garbage: settings.garbage })
+        {
+            Ok(session) => Ok(Transition::Reset(Box::new(Versus::online(settings, session)))),
+            Err(e) =>
+            {
+                self.error = Some(e.to_string());
+                Ok(Transition::None)
+            }
+        }
+    }
+    fn draw(&mut self, context: &mut Context, _shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        let mut text = format!("ONLINE VERSUS\n\nWaiting for a player on port {}\nPress back to cancel", self.port);
+        if let Some(error) = &self.error
+        {
+            text.push_str(&format!("\n\nA player failed to join: {}", error));
+        }
+        renderer.draw_text(context, &text, Layout::menu_position(graphics::screen_coordinates(context)));
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use ggez::{Context, GameResult};
+use game_core::{Bot, BotPlayer, GameMode};
+use crate::game::Game;
+use crate::game::versus::Versus;
+use crate::game::renderer::Renderer;
+use super::{Scene, Shared, Transition};
+use super::menu::Menu;
+use super::mode_select::ModeSelect;
+use super::settings::SettingsMenu;
+use super::high_scores::HighScores;
+
+pub struct MainMenu
+{
+    menu: Menu
+}
+impl MainMenu
+{
+    const PLAY: usize = 0;
+    const VERSUS: usize = 1;
+    const WATCH_BOT: usize = 2;
+    const SETTINGS: usize = 3;
+    const HIGH_SCORES: usize = 4;
+    const QUIT: usize = 5;
+
+    pub fn new() -> MainMenu
+    {
+        MainMenu { menu: Menu::new("TETRIS", &["Play", "Versus", "Watch bot", "Settings", "High scores", "Quit"]) }
+    }
+}
+impl Scene for MainMenu
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        Ok(match self.menu.update(&shared.input)
+        {
+            Some(MainMenu::PLAY) => Transition::Push(Box::new(ModeSelect::new())),
+            Some(MainMenu::VERSUS) => Transition::Push(Box::new(Versus::new(&shared.settings))),
+            Some(MainMenu::WATCH_BOT) => Transition::Push(Box::new(Game::new(shared, GameMode::Endless, None, Some(BotPlayer::new(Bot::default()))))),
+            Some(MainMenu::SETTINGS) => Transition::Push(Box::new(SettingsMenu::new(shared))),
+            Some(MainMenu::HIGH_SCORES) => Transition::Push(Box::new(HighScores::new())),
+            Some(MainMenu::QUIT) => Transition::Quit,
+            _ => Transition::None
+        })
+    }
+    fn draw(&mut self, context: &mut Context, _shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        self.menu.draw(context, renderer);
+        Ok(())
+    }
+}
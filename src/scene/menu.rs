@@ -0,0 +1,51 @@
+use ggez::{Context, graphics};
+use crate::game::input::Input;
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use crate::game::layout::Layout;
+
+//A vertical list of items, moved through with the menu actions
+pub struct Menu
+{
+    title: String,
+    items: Vec<String>,
+    selected: usize
+}
+impl Menu
+{
+    pub fn new(title: &str, items: &[&str]) -> Menu
+    {
+        Menu { title: title.to_string(), items: items.iter().map(|item| item.to_string()).collect(), selected: 0 }
+    }
+    pub fn selected(&self) -> usize
+    {
+        self.selected
+    }
+    pub fn set_item(&mut self, index: usize, text: &str)
+    {
+        self.items[index] = text.to_string();
+    }
+    //Moves the selection, wrapping around, and returns the item that was confirmed if any
+    pub fn update(&mut self, input: &Input) -> Option<usize>
+    {
+        if input.get_action_down(Action::MenuUp)
+        {
+            self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+        }
+        if input.get_action_down(Action::MenuDown)
+        {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+        if input.get_action_down(Action::Confirm) { Some(self.selected) } else { None }
+    }
+    pub fn draw(&self, context: &mut Context, renderer: &mut Renderer)
+    {
+        let mut text = format!("{}\n", self.title);
+        for (i, item) in self.items.iter().enumerate()
+        {
+            text.push_str(if i == self.selected { "\n> " } else { "\n  " });
+            text.push_str(item);
+        }
+        renderer.draw_text(context, &text, Layout::menu_position(graphics::screen_coordinates(context)));
+    }
+}
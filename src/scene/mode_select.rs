@@ -0,0 +1,56 @@
+use ggez::{Context, GameResult};
+use game_core::GameMode;
+use crate::game::Game;
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use super::{Scene, Shared, Transition};
+use super::menu::Menu;
+
+//Picks the mode for a new game, the last item goes back
+pub struct ModeSelect
+{
+    menu: Menu
+}
+impl ModeSelect
+{
+    pub fn new() -> ModeSelect
+    {
+        let mut items: Vec<String> = GameMode::SELECTABLE.iter().map(|mode| format!("{} - {}", mode.name(), ModeSelect::description(*mode))).collect();
+        items.push("Back".to_string());
+        let items: Vec<&str> = items.iter().map(|item| item.as_str()).collect();
+        ModeSelect { menu: Menu::new("SELECT MODE", &items) }
+    }
+    fn description(mode: GameMode) -> &'static str
+    {
+        match mode
+        {
+            GameMode::Sprint => "clear 40 lines as fast as you can",
+            GameMode::Ultra => "score as much as you can in 2 minutes",
+            GameMode::Marathon => "clear 150 lines, up to level 15",
+            GameMode::Zen => "no goal, no game over",
+            GameMode::Endless => "play until you top out",
+            GameMode::Versus => "send garbage until the other player tops out"
+        }
+    }
+}
+impl Scene for ModeSelect
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        if shared.input.get_action_down(Action::Back)
+        {
+            return Ok(Transition::Pop(None));
+        }
+        Ok(match self.menu.update(&shared.input)
+        {
+            Some(i) if i < GameMode::SELECTABLE.len() => Transition::Push(Box::new(Game::new(shared, GameMode::SELECTABLE[i], None, None))),
+            Some(_) => Transition::Pop(None),
+            None => Transition::None
+        })
+    }
+    fn draw(&mut self, context: &mut Context, _shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        self.menu.draw(context, renderer);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use ggez::{Context, GameResult};
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use super::{Scene, Shared, Transition, Command};
+use super::menu::Menu;
+use super::main_menu::MainMenu;
+use super::settings::SettingsMenu;
+
+pub struct PauseMenu
+{
+    menu: Menu
+}
+impl PauseMenu
+{
+    const RESUME: usize = 0;
+    const RESTART: usize = 1;
+    const SETTINGS: usize = 2;
+    const MAIN_MENU: usize = 3;
+
+    pub fn new() -> PauseMenu
+    {
+        PauseMenu { menu: Menu::new("PAUSED", &["Resume", "Restart", "Settings", "Main menu"]) }
+    }
+}
+impl Scene for PauseMenu
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        if shared.input.get_action_down(Action::Pause) || shared.input.get_action_down(Action::Back)
+        {
+            return Ok(Transition::Pop(None));
+        }
+        Ok(match self.menu.update(&shared.input)
+        {
+            Some(PauseMenu::RESUME) => Transition::Pop(None),
+            Some(PauseMenu::RESTART) => Transition::Pop(Some(Command::Restart)),
+            Some(PauseMenu::SETTINGS) => Transition::Push(Box::new(SettingsMenu::new(shared))),
+            Some(PauseMenu::MAIN_MENU) => Transition::Reset(Box::new(MainMenu::new())),
+            _ => Transition::None
+        })
+    }
+    fn draw(&mut self, context: &mut Context, _shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        renderer.draw_overlay(context, 0.6);
+        self.menu.draw(context, renderer);
+        Ok(())
+    }
+    fn is_overlay(&self) -> bool
+    {
+        true
+    }
+}
@@ -0,0 +1,88 @@
+use ggez::{Context, GameResult};
+use std::ops::RangeInclusive;
+use game_core::{GarbageRules, Rules};
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use super::{Scene, Shared, Transition};
+use super::menu::Menu;
+
+//Left and right change the selected value, changes are kept as soon as they are made
+pub struct SettingsMenu
+{
+    menu: Menu
+}
+impl SettingsMenu
+{
+    const DAS: usize = 0;
+    const ARR: usize = 1;
+    const LOCK_DELAY: usize = 2;
+    const BOARD_WIDTH: usize = 3;
+    const BOARD_HEIGHT: usize = 4;
+    const GHOST_PIECE: usize = 5;
+    const THEME: usize = 6;
+    const GARBAGE_MESSINESS: usize = 7;
+    const BACK: usize = 8;
+
+    pub fn new(shared: &Shared) -> SettingsMenu
+    {
+        let mut menu = SettingsMenu { menu: Menu::new("SETTINGS\nRules apply from the next game", &[""; 9]) };
+        menu.refresh(shared);
+        menu
+    }
+    fn refresh(&mut self, shared: &Shared)
+    {
+        let settings = &shared.settings;
+        let rules = &settings.rules;
+        self.menu.set_item(SettingsMenu::DAS, &format!("DAS: {} frames", rules.das));
+        self.menu.set_item(SettingsMenu::ARR, &format!("ARR: {} frames", rules.arr));
+        self.menu.set_item(SettingsMenu::LOCK_DELAY, &format!("Lock delay: {} frames", rules.lock_delay));
+        self.menu.set_item(SettingsMenu::BOARD_WIDTH, &format!("Board width: {}", rules.width));
+        self.menu.set_item(SettingsMenu::BOARD_HEIGHT, &format!("Board height: {}", rules.height));
+        self.menu.set_item(SettingsMenu::GHOST_PIECE, &format!("Ghost piece: {}", if settings.ghost_piece { "On" } else { "Off" }));
+        self.menu.set_item(SettingsMenu::THEME, &format!("Theme: {}", shared.theme().name));
+        self.menu.set_item(SettingsMenu::GARBAGE_MESSINESS, &format!("Versus garbage messiness: {}%", settings.garbage.messiness));
+        self.menu.set_item(SettingsMenu::BACK, "Back");
+    }
+    fn adjust(value: u32, change: i32, step: u32, range: RangeInclusive<u32>) -> u32
+    {
+        (value as i32 + change * step as i32).clamp(*range.start() as i32, *range.end() as i32) as u32
+    }
+}
+impl Scene for SettingsMenu
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        if shared.input.get_action_down(Action::Back)
+        {
+            return Ok(Transition::Pop(None));
+        }
+        let confirmed = self.menu.update(&shared.input);
+        let change = shared.input.get_action_down(Action::MoveRight) as i32 - shared.input.get_action_down(Action::MoveLeft) as i32;
+        let settings = &mut shared.settings;
+        match self.menu.selected()
+        {
+            SettingsMenu::DAS => settings.rules.das = SettingsMenu::adjust(settings.rules.das, change, 1, Rules::DAS),
+            SettingsMenu::ARR => settings.rules.arr = SettingsMenu::adjust(settings.rules.arr, change, 1, Rules::ARR),
+            SettingsMenu::LOCK_DELAY => settings.rules.lock_delay = SettingsMenu::adjust(settings.rules.lock_delay, change, 5, Rules::LOCK_DELAY),
+            SettingsMenu::BOARD_WIDTH => settings.rules.width = SettingsMenu::adjust(settings.rules.width, change, 1, Rules::WIDTH),
+            SettingsMenu::BOARD_HEIGHT => settings.rules.height = SettingsMenu::adjust(settings.rules.height, change, 1, Rules::HEIGHT),
+            SettingsMenu::GHOST_PIECE if change != 0 || confirmed.is_some() => settings.ghost_piece = !settings.ghost_piece,
+            //Wraps around, the change shows right away
+            SettingsMenu::THEME =>
+            {
+                let count = shared.themes.len() as i32;
+                settings.theme = (settings.theme as i32 + change + confirmed.is_some() as i32).rem_euclid(count) as usize;
+            }
+            SettingsMenu::GARBAGE_MESSINESS => settings.garbage.messiness = SettingsMenu::adjust(settings.garbage.messiness, change, 10, GarbageRules::MESSINESS),
+            SettingsMenu::BACK if confirmed.is_some() => return Ok(Transition::Pop(None)),
+            _ => {}
+        }
+        self.refresh(shared);
+        Ok(Transition::None)
+    }
+    fn draw(&mut self, context: &mut Context, _shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        self.menu.draw(context, renderer);
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use ggez::{Context, GameResult};
+use game_core::GameState;
+use crate::game::input::bindings::Action;
+use crate::game::renderer::Renderer;
+use crate::game::hud::Hud;
+use super::{Scene, Shared, Transition, Command};
+use super::menu::Menu;
+use super::main_menu::MainMenu;
+
+pub struct VersusOverMenu
+{
+    menu: Menu,
+    rematch: bool
+}
+impl VersusOverMenu
+{
+    const REMATCH: usize = 0;
+
+    //The player still standing wins, both topping out on the same update is a draw. Online games can't be restarted from here
+    pub fn new(players: [&GameState; 2], rematch: bool) -> VersusOverMenu
+    {
+        let mut title = match (players[0].is_game_over(), players[1].is_game_over())
+        {
+            (false, true) => "PLAYER 1 WINS".to_string(),
+            (true, false) => "PLAYER 2 WINS".to_string(),
+            _ => "DRAW".to_string()
+        };
+        title.push_str(&format!("\nTime: {}\n", Hud::format_time(players[0].elapsed())));
+        for (i, state) in players.iter().enumerate()
+        {
+            title.push_str(&format!("\nPlayer {}  Sent: {}  Lines: {}  Pieces: {}", i + 1, state.lines_sent(), state.score().lines(), state.pieces_placed()));
+        }
+        VersusOverMenu::with_title(&title, rematch)
+    }
+    pub fn connection_lost(reason: &str) -> VersusOverMenu
+    {
+        VersusOverMenu::with_title(&format!("CONNECTION LOST\n{}", reason), false)
+    }
+    fn with_title(title: &str, rematch: bool) -> VersusOverMenu
+    {
+        let items: &[&str] = if rematch { &["Rematch", "Main menu"] } else { &["Main menu"] };
+        VersusOverMenu { menu: Menu::new(title, items), rematch }
+    }
+}
+impl Scene for VersusOverMenu
+{
+    fn update(&mut self, _context: &mut Context, shared: &mut Shared) -> GameResult<Transition>
+    {
+        if self.rematch && shared.input.get_action_down(Action::Restart)
+        {
+            return Ok(Transition::Pop(Some(Command::Restart)));
+        }
+        Ok(match self.menu.update(&shared.input)
+        {
+            Some(VersusOverMenu::REMATCH) if self.rematch => Transition::Pop(Some(Command::Restart)),
+            Some(_) => Transition::Reset(Box::new(MainMenu::new())),
+            None => Transition::None
+        })
+    }
+    fn draw(&mut self, context: &mut Context, _shared: &Shared, renderer: &mut Renderer) -> GameResult<()>
+    {
+        renderer.draw_overlay(context, 0.8);
+        self.menu.draw(context, renderer);
+        Ok(())
+    }
+    fn is_overlay(&self) -> bool
+    {
+        true
+    }
+}